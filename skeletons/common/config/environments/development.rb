@@ -0,0 +1,8 @@
+require "active_support/core_ext/integer/time"
+
+Rails.application.configure do
+  # Settings specified here will take precedence over those in config/application.rb.
+  config.enable_reloading = true
+  config.eager_load = false
+  config.consider_all_requests_local = true
+end
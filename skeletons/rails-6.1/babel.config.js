@@ -0,0 +1,12 @@
+module.exports = function(api) {
+  var validEnv = ['development', 'test', 'production']
+  var currentEnv = api.env()
+
+  if (!validEnv.includes(currentEnv)) {
+    throw new Error('Please specify a valid `NODE_ENV` or `BABEL_ENV` environment variables.')
+  }
+
+  return {
+    presets: [['@babel/preset-env', { useBuiltIns: 'entry', corejs: 3 }]]
+  }
+}
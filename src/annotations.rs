@@ -0,0 +1,138 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Where and how the `# @team` comment sits at the top of an annotated file. Tools scan the
+/// leading comment block, so each style puts something different in front of or around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnnotationStyle {
+    /// `# @team` on the first line.
+    Plain,
+    /// After the magic comment and a blank line, as rubocop leaves most files.
+    AfterFrozenStringLiteral,
+    /// After an encoding comment and the frozen string literal comment.
+    AfterEncoding,
+    /// Below a `#!` line, as in executable scripts.
+    AfterShebang,
+    /// Preceded by blank lines and followed by trailing spaces.
+    PaddedWhitespace,
+}
+
+impl AnnotationStyle {
+    const ALL: [Self; 5] = [
+        Self::Plain,
+        Self::AfterFrozenStringLiteral,
+        Self::AfterEncoding,
+        Self::AfterShebang,
+        Self::PaddedWhitespace,
+    ];
+
+    pub(crate) fn random(rng: &mut impl Rng) -> Self {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())]
+    }
+
+    /// The lines a file starts with, ending in the annotation for `team`.
+    pub(crate) fn header(self, team: &str) -> String {
+        match self {
+            Self::Plain => format!("# @team {}\n", team),
+            Self::AfterFrozenStringLiteral => {
+                format!("# frozen_string_literal: true\n\n# @team {}\n", team)
+            }
+            Self::AfterEncoding => format!(
+                "# encoding: utf-8\n# frozen_string_literal: true\n# @team {}\n",
+                team
+            ),
+            Self::AfterShebang => format!(
+                "#!/usr/bin/env ruby\n# frozen_string_literal: true\n\n# @team {}\n",
+                team
+            ),
+            Self::PaddedWhitespace => format!("\n\n# @team {}   \n", team),
+        }
+    }
+}
+
+/// Lines in [`EdgeCase::AnnotationOnLastLine`], enough that tools reading the whole file
+/// instead of its leading comments show up in profiles.
+const HUGE_FILE_LINES: usize = 10_000;
+
+/// A file written to trip up annotation parsers, see [`crate::Config::pathological_annotations`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeCase {
+    /// `# @team` text inside a string in a method body.
+    TeamInMethodBody,
+    /// `# @team` lines inside a heredoc.
+    TeamInHeredoc,
+    /// Two leading annotations naming different teams.
+    ConflictingAnnotations,
+    /// A huge file whose only annotation is its last line.
+    AnnotationOnLastLine,
+}
+
+/// What a well-behaved tool does with an [`EdgeCase`] file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeCaseExpectation {
+    /// The stray `@team` text doesn't count: the file is owned by its leading annotation,
+    /// or by nobody when it has none.
+    Ignored,
+    /// The file is reported as an error.
+    Error,
+}
+
+impl EdgeCase {
+    pub const ALL: [Self; 4] = [
+        Self::TeamInMethodBody,
+        Self::TeamInHeredoc,
+        Self::ConflictingAnnotations,
+        Self::AnnotationOnLastLine,
+    ];
+
+    pub fn expectation(self) -> EdgeCaseExpectation {
+        match self {
+            Self::ConflictingAnnotations => EdgeCaseExpectation::Error,
+            _ => EdgeCaseExpectation::Ignored,
+        }
+    }
+
+    /// Whether the file is owned by the pack's team: it has a leading annotation and isn't
+    /// an error.
+    pub(crate) fn owned(self) -> bool {
+        matches!(self, Self::TeamInMethodBody | Self::TeamInHeredoc)
+    }
+
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            Self::TeamInMethodBody => "team_in_method_body.rb",
+            Self::TeamInHeredoc => "team_in_heredoc.rb",
+            Self::ConflictingAnnotations => "conflicting_annotations.rb",
+            Self::AnnotationOnLastLine => "annotation_on_last_line.rb",
+        }
+    }
+
+    /// The file contents. `team` owns the file; `other` is the team the stray annotations
+    /// name.
+    pub(crate) fn contents(self, team: &str, other: &str) -> String {
+        match self {
+            Self::TeamInMethodBody => format!(
+                "# @team {}\nclass TeamInMethodBody\n  def owner_comment\n    \"# @team {}\"\n  end\nend\n",
+                team, other
+            ),
+            Self::TeamInHeredoc => format!(
+                "# @team {}\nclass TeamInHeredoc\n  TEMPLATE = <<~RUBY\n# @team {}\n    # @team {}\n  RUBY\nend\n",
+                team, other, other
+            ),
+            Self::ConflictingAnnotations => format!(
+                "# @team {}\n# @team {}\nclass ConflictingAnnotations\nend\n",
+                team, other
+            ),
+            Self::AnnotationOnLastLine => {
+                let mut contents = String::from("class AnnotationOnLastLine\n");
+                for line in 0..HUGE_FILE_LINES {
+                    contents.push_str(&format!("  def method_{}; end\n", line));
+                }
+                contents.push_str(&format!("end\n# @team {}\n", other));
+                contents
+            }
+        }
+    }
+}
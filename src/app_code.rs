@@ -0,0 +1,116 @@
+use convert_case::{Case, Casing};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    annotations::AnnotationStyle, manifest::AppCodeDir, random_name, Config, PackOwnership,
+};
+
+/// Salt for the RNG behind the app's own code, so it doesn't correlate with the first pack
+/// drawn from the same seed.
+const APP_CODE_SEED_SALT: u64 = 0x6170_7063_6F64_6521;
+
+const FILES_PER_APP_CODE_DIR: usize = 10;
+
+/// The `app` directories that get domain subdirectories, and the superclass of their files.
+const APP_CODE_KINDS: &[(&str, Option<&str>)] = &[
+    ("models", Some("ApplicationRecord")),
+    ("controllers", Some("ApplicationController")),
+    ("services", None),
+];
+
+/// What the infra team owns under `app` when the app has code of its own: the skeleton's
+/// files, leaving the domain directories to their teams.
+pub(crate) const INFRA_APP_GLOBS: &[&str] = &[
+    "app/*/application*",
+    "app/assets/**",
+    "app/channels/**",
+    "app/javascript/**",
+    "app/views/**",
+];
+
+/// Writes [`Config::app_code_dirs`] domain directories of plain Rails code outside the packs,
+/// each owned either through `# @team` annotations naming one of `teams` or through an
+/// `owned_globs` entry of its own `<domain>-<kind>-core-team`. Returns the directories and the
+/// number of files written.
+pub(crate) fn write(
+    config: &Config,
+    seed: u64,
+    teams: &[String],
+) -> anyhow::Result<(Vec<AppCodeDir>, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed ^ APP_CODE_SEED_SALT);
+    let app_dir = config.app_dir();
+    let mut dirs: Vec<AppCodeDir> = Vec::new();
+    let mut files = 0;
+    while dirs.len() < config.app_code_dirs {
+        let domain = random_name(&mut rng);
+        let (kind, superclass) = APP_CODE_KINDS[rng.gen_range(0..APP_CODE_KINDS.len())];
+        let path = std::path::Path::new("app").join(kind).join(&domain);
+        if dirs.iter().any(|dir| dir.path == path) {
+            continue;
+        }
+        let annotated = !teams.is_empty() && rng.gen_bool(0.5);
+        let team = if annotated {
+            teams[rng.gen_range(0..teams.len())].clone()
+        } else {
+            // The same domain can turn up under another kind, and needs a team of its own
+            // there.
+            let team = format!("{}-{}-core-team", domain, kind);
+            let yaml = format!(
+                "name: {}\ngithub:\n  team: '@{}'\n  members:\n{}\nowned_globs:\n  - \"{}/**\"\n",
+                team,
+                team,
+                config
+                    .team_size
+                    .members(&mut rng)
+                    .iter()
+                    .map(|member| format!("    - {}\n", member))
+                    .collect::<String>(),
+                path.display()
+            );
+            // Rewritten from scratch on resume, so a combined team file doesn't get the
+            // team twice.
+            config.team_layout.remove(&app_dir, &team)?;
+            config.team_layout.write(&app_dir, &team, &yaml)?;
+            files += 1;
+            team
+        };
+
+        let full_path = app_dir.join(&path);
+        std::fs::create_dir_all(&full_path)?;
+        let module = domain.to_case(Case::Pascal);
+        for _ in 0..FILES_PER_APP_CODE_DIR {
+            let name = random_name(&mut rng);
+            let (file_name, class) = match kind {
+                "controllers" => (
+                    format!("{}_controller.rb", name),
+                    format!("{}Controller", name.to_case(Case::Pascal)),
+                ),
+                _ => (format!("{}.rb", name), name.to_case(Case::Pascal)),
+            };
+            let mut contents = String::new();
+            if annotated {
+                contents.push_str(&AnnotationStyle::Plain.header(&team));
+            }
+            let declaration = match superclass {
+                Some(superclass) => format!("{} < {}", class, superclass),
+                None => class,
+            };
+            contents.push_str(&format!(
+                "module {}\n  class {}\n  end\nend\n",
+                module, declaration
+            ));
+            std::fs::write(full_path.join(file_name), contents)?;
+        }
+        files += FILES_PER_APP_CODE_DIR;
+        dirs.push(AppCodeDir {
+            path,
+            team,
+            ownership: if annotated {
+                PackOwnership::FileAnnotation
+            } else {
+                PackOwnership::TeamConfig
+            },
+        });
+    }
+    Ok((dirs, files))
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    tools::{self, TOOL_COMMANDS},
+    Manifest, ToolOutcome,
+};
+
+/// What to benchmark, see [`run_bench`].
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// A generated app, with the tools to time in its `.dotslash` directory.
+    pub app_dir: PathBuf,
+    /// Timed runs per tool. The median is what gets stored and compared.
+    pub runs: usize,
+    /// Version labels to record instead of the ones in the app's manifest, keyed by tool
+    /// name. Needed when a locally built binary was dropped into `.dotslash`.
+    pub tool_versions: BTreeMap<String, String>,
+    /// Have this hyperfine binary take the measurements instead of timing the runs here.
+    pub hyperfine: Option<String>,
+}
+
+/// Timings for one tool against one fixture. Baselines are keyed by `tool`, `version` and
+/// `seed`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub tool: String,
+    pub version: String,
+    /// The seed of the fixture the tool ran against.
+    pub seed: u64,
+    pub command: String,
+    pub runs: usize,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// Seconds since the Unix epoch when the result was taken.
+    pub recorded_at: u64,
+}
+
+/// Runs every tool in [`TOOL_COMMANDS`] against the app `config.runs` times. Fails when a run
+/// doesn't pass, since the timing of a failed validation says nothing about performance.
+pub fn run_bench(config: &BenchConfig) -> anyhow::Result<Vec<BenchResult>> {
+    anyhow::ensure!(config.runs > 0, "at least one run is needed");
+    let manifest = Manifest::load(&config.app_dir).with_context(|| {
+        format!(
+            "{} is not a generated app (no readable manifest)",
+            config.app_dir.display()
+        )
+    })?;
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut results = Vec::new();
+    for (tool, program, args) in TOOL_COMMANDS {
+        let version = match config.tool_versions.get(*tool) {
+            Some(version) => version.clone(),
+            None => manifest
+                .tools
+                .iter()
+                .find(|manifest_tool| manifest_tool.name == *tool)
+                .map(|manifest_tool| {
+                    manifest_tool
+                        .version
+                        .clone()
+                        .unwrap_or_else(|| manifest_tool.url.clone())
+                })
+                .unwrap_or_else(|| "unknown".to_string()),
+        };
+        let timings = match &config.hyperfine {
+            Some(hyperfine) => measure_with_hyperfine(hyperfine, config, tool, program, args)?,
+            None => measure(config, program, args)?,
+        };
+        results.push(BenchResult {
+            tool: tool.to_string(),
+            version,
+            seed: manifest.seed,
+            command: std::iter::once(*program)
+                .chain(args.iter().copied())
+                .collect::<Vec<_>>()
+                .join(" "),
+            runs: config.runs,
+            median_ms: timings.median_ms,
+            min_ms: timings.min_ms,
+            max_ms: timings.max_ms,
+            recorded_at,
+        });
+    }
+    Ok(results)
+}
+
+struct Timings {
+    median_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+}
+
+fn measure(config: &BenchConfig, program: &str, args: &[&str]) -> anyhow::Result<Timings> {
+    let mut timings = Vec::with_capacity(config.runs);
+    for _ in 0..config.runs {
+        let (run, elapsed) = tools::run_tool(&config.app_dir, program, args);
+        match run.outcome {
+            ToolOutcome::Passed => {}
+            ToolOutcome::Failed { exit_code, stderr } => anyhow::bail!(
+                "{} failed with exit code {}: {}",
+                run.command,
+                exit_code,
+                stderr
+            ),
+            ToolOutcome::Crashed { reason } => {
+                anyhow::bail!("{} crashed: {}", run.command, reason)
+            }
+        }
+        timings.push(elapsed);
+    }
+    timings.sort();
+    Ok(Timings {
+        median_ms: millis(timings[timings.len() / 2]),
+        min_ms: millis(timings[0]),
+        max_ms: millis(timings[timings.len() - 1]),
+    })
+}
+
+/// The part of hyperfine's `--export-json` output that gets imported. Times are in seconds.
+#[derive(Deserialize)]
+struct HyperfineExport {
+    results: Vec<HyperfineResult>,
+}
+
+#[derive(Deserialize)]
+struct HyperfineResult {
+    median: f64,
+    min: f64,
+    max: f64,
+}
+
+/// Runs `program` under hyperfine from the app directory and imports the timings it exports.
+/// hyperfine fails the whole run when the tool exits non-zero, like [`measure`] does.
+fn measure_with_hyperfine(
+    hyperfine: &str,
+    config: &BenchConfig,
+    tool: &str,
+    program: &str,
+    args: &[&str],
+) -> anyhow::Result<Timings> {
+    let program_path = std::path::absolute(config.app_dir.join(program))?;
+    let command_line = std::iter::once(shell_quote(&program_path.to_string_lossy()))
+        .chain(args.iter().map(|arg| shell_quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let export = tempfile::NamedTempFile::new()?;
+    let output = Command::new(hyperfine)
+        .arg("--runs")
+        .arg(config.runs.to_string())
+        .arg("--style")
+        .arg("none")
+        .arg("--command-name")
+        .arg(tool)
+        .arg("--export-json")
+        .arg(export.path())
+        .arg(&command_line)
+        .current_dir(&config.app_dir)
+        .output()
+        .with_context(|| format!("failed to start {}", hyperfine))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "hyperfine {} failed ({}): {}",
+        command_line,
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let export: HyperfineExport = serde_json::from_str(&std::fs::read_to_string(export.path())?)
+        .context("unreadable hyperfine JSON export")?;
+    let result = export
+        .results
+        .into_iter()
+        .next()
+        .context("hyperfine exported no results")?;
+    Ok(Timings {
+        median_ms: result.median * 1000.0,
+        min_ms: result.min * 1000.0,
+        max_ms: result.max * 1000.0,
+    })
+}
+
+/// Quotes `word` for the POSIX shell hyperfine runs commands through.
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Stored [`BenchResult`]s, one per tool, version and seed, kept in a JSON file that can be
+/// checked in or cached between CI runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baselines {
+    pub results: Vec<BenchResult>,
+}
+
+impl Baselines {
+    /// Loads baselines from `path`, or starts empty when the file doesn't exist yet.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("unreadable baselines in {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Stores `result`, replacing any earlier result for the same tool, version and seed.
+    pub fn record(&mut self, result: BenchResult) {
+        self.results.retain(|stored| {
+            (&stored.tool, &stored.version, stored.seed)
+                != (&result.tool, &result.version, result.seed)
+        });
+        self.results.push(result);
+    }
+
+    /// The baseline to compare `current` against: the most recently recorded result for the
+    /// same tool and seed, restricted to `version` when given.
+    pub fn find(&self, current: &BenchResult, version: Option<&str>) -> Option<&BenchResult> {
+        self.results
+            .iter()
+            .filter(|stored| stored.tool == current.tool && stored.seed == current.seed)
+            .filter(|stored| version.is_none_or(|version| stored.version == version))
+            .max_by_key(|stored| stored.recorded_at)
+    }
+}
+
+/// A new result next to its baseline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub tool: String,
+    pub baseline_version: String,
+    pub version: String,
+    pub baseline_ms: f64,
+    pub median_ms: f64,
+    /// Positive when the new run is slower.
+    pub change_percent: f64,
+    /// Slower than the baseline by more than the threshold.
+    pub regressed: bool,
+}
+
+/// Compares each result to its baseline. Results without a baseline are left out.
+pub fn compare(
+    results: &[BenchResult],
+    baselines: &Baselines,
+    baseline_version: Option<&str>,
+    threshold_percent: f64,
+) -> Vec<Comparison> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let baseline = baselines.find(result, baseline_version)?;
+            let change_percent =
+                (result.median_ms - baseline.median_ms) / baseline.median_ms * 100.0;
+            Some(Comparison {
+                tool: result.tool.clone(),
+                baseline_version: baseline.version.clone(),
+                version: result.version.clone(),
+                baseline_ms: baseline.median_ms,
+                median_ms: result.median_ms,
+                change_percent,
+                regressed: change_percent > threshold_percent,
+            })
+        })
+        .collect()
+}
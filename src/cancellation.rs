@@ -0,0 +1,50 @@
+use serde::Serialize;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Shared flag used to stop generation early. Clones observe the same flag, so one clone can
+/// live in [`crate::Config`] while another is cancelled from a signal handler or another thread.
+///
+/// Generation only checks the flag between phases and between packs, so a cancelled run never
+/// leaves a half-written pack behind.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Returned (wrapped in `anyhow::Error`) when generation stops because its
+/// [`CancellationToken`] was cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Cancelled {
+    pub completed_packs: usize,
+    pub total_packs: usize,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "generation cancelled after {} of {} packs",
+            self.completed_packs, self.total_packs
+        )
+    }
+}
+
+impl std::error::Error for Cancelled {}
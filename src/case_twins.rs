@@ -0,0 +1,42 @@
+use std::path::Path;
+
+/// Name of the file in `app/services/a` that gets a capitalized twin.
+pub(crate) const TWIN_FILE_STEM: &str = "billing";
+
+/// `name` with its first letter upper-cased, e.g. `billing` to `Billing`.
+pub(crate) fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Writes `contents` to `lower`, then to `upper` when the filesystem keeps the two apart.
+/// On a case-insensitive filesystem `upper` already resolves to `lower`, so it is skipped.
+/// Returns whether the twin was written.
+pub(crate) fn write_twin(lower: &Path, upper: &Path, contents: &str) -> anyhow::Result<bool> {
+    if let Some(parent) = lower.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(lower, contents)?;
+    if upper.exists() {
+        return Ok(false);
+    }
+    if let Some(parent) = upper.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(upper, contents)?;
+    Ok(true)
+}
+
+/// Whether `path` exists under exactly this spelling, which on a case-insensitive filesystem
+/// `Path::exists` can't tell.
+pub(crate) fn exists_exactly(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    std::fs::read_dir(parent)
+        .map(|entries| entries.flatten().any(|entry| entry.file_name() == name))
+        .unwrap_or(false)
+}
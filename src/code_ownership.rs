@@ -0,0 +1,243 @@
+use std::{fmt, str::FromStr};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{ownership_table::glob_matches, Config, PackageRoot};
+
+pub(crate) const CODE_OWNERSHIP_FILE: &str = "config/code_ownership.yml";
+
+/// Salt for picking a shape with [`CodeOwnershipShape::Random`], so the choice doesn't
+/// correlate with the first pack drawn from the same seed.
+const SHAPE_SEED_SALT: u64 = 0x636F_6E66_6967_7368;
+
+/// Which `config/code_ownership.yml` template the app gets. Real apps drift from the
+/// documented example in many ways, and tools should read all of them the same way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeOwnershipShape {
+    /// Every documented key with the usual globs.
+    #[default]
+    Standard,
+    /// Only the required keys, leaving the rest to tool defaults.
+    Minimal,
+    /// The standard keys plus ones tools may not know about and should ignore.
+    ExtraKeys,
+    /// Narrower owned globs and non-default vendored gem and JavaScript package paths.
+    Alternate,
+    /// One of the other shapes, picked from the run's seed.
+    Random,
+}
+
+impl FromStr for CodeOwnershipShape {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "minimal" => Ok(Self::Minimal),
+            "extra-keys" => Ok(Self::ExtraKeys),
+            "alternate" => Ok(Self::Alternate),
+            "random" => Ok(Self::Random),
+            _ => anyhow::bail!(
+                "unknown code_ownership.yml shape '{}' (expected standard, minimal, extra-keys, alternate or random)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for CodeOwnershipShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Standard => "standard",
+            Self::Minimal => "minimal",
+            Self::ExtraKeys => "extra-keys",
+            Self::Alternate => "alternate",
+            Self::Random => "random",
+        })
+    }
+}
+
+impl CodeOwnershipShape {
+    /// Replaces [`Self::Random`] with the shape `seed` picks. Other shapes are returned as is.
+    pub(crate) fn resolve(self, seed: u64) -> Self {
+        if self != Self::Random {
+            return self;
+        }
+        match StdRng::seed_from_u64(seed ^ SHAPE_SEED_SALT).gen_range(0..4) {
+            0 => Self::Standard,
+            1 => Self::Minimal,
+            2 => Self::ExtraKeys,
+            _ => Self::Alternate,
+        }
+    }
+
+    /// The file contents for a resolved shape, with [`Config::extra_owned_globs`] and
+    /// [`Config::extra_unowned_globs`] merged into the template's lists. Ruby files under
+    /// [`Config::package_roots`] the template's globs miss get a glob of their own, and a `gems`
+    /// root moves `vendored_gems_path` out of its way.
+    pub(crate) fn render(self, config: &Config) -> String {
+        let template = match self {
+            Self::Standard | Self::Random => STANDARD,
+            Self::Minimal => MINIMAL,
+            Self::ExtraKeys => EXTRA_KEYS,
+            Self::Alternate => ALTERNATE,
+        };
+        let template_globs = owned_globs(template);
+        let owned_globs = config
+            .package_roots
+            .iter()
+            .filter(|root| {
+                let file = format!("{}/pack/app/models/model.rb", root.dir());
+                !template_globs
+                    .iter()
+                    .flat_map(|glob| expand_braces(glob))
+                    .any(|glob| glob_matches(&glob, &file))
+            })
+            .map(|root| format!("{}/**/*.rb", root.dir()))
+            .chain(config.extra_owned_globs.iter().cloned())
+            .collect::<Vec<_>>();
+        let mut body = append_entries(template, "owned_globs", &owned_globs);
+        body = append_entries(&body, "unowned_globs", &config.extra_unowned_globs);
+        if config.package_roots.contains(&PackageRoot::Gems) {
+            body = move_vendored_gems(&body);
+        }
+        format!(
+            "{}team_file_glob:\n  - {}\n",
+            body,
+            config.team_layout.team_file_glob()
+        )
+    }
+}
+
+/// The `owned_globs` of a template.
+fn owned_globs(template: &str) -> Vec<String> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(template).expect("templates are YAML");
+    yaml["owned_globs"]
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(|glob| glob.as_str().map(str::to_string))
+        .collect()
+}
+
+/// `glob` with each `{a,b}` group expanded, one glob per alternative.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some((prefix, rest)) = glob.split_once('{') else {
+        return vec![glob.to_string()];
+    };
+    let Some((alternatives, suffix)) = rest.split_once('}') else {
+        return vec![glob.to_string()];
+    };
+    alternatives
+        .split(',')
+        .flat_map(|alternative| expand_braces(&format!("{}{}{}", prefix, alternative, suffix)))
+        .collect()
+}
+
+/// Points `vendored_gems_path` at `vendor/gems` so packs under a `gems` package root aren't
+/// mistaken for vendored gems, whether the template sets it to `gems` or leaves the default.
+fn move_vendored_gems(yaml: &str) -> String {
+    if yaml.lines().any(|line| line == VENDORED_GEMS) {
+        return yaml.replace(VENDORED_GEMS, MOVED_VENDORED_GEMS);
+    }
+    if yaml
+        .lines()
+        .any(|line| line.starts_with("vendored_gems_path:"))
+    {
+        return yaml.to_string();
+    }
+    format!("{}{}\n", yaml, MOVED_VENDORED_GEMS)
+}
+
+const VENDORED_GEMS: &str = "vendored_gems_path: gems";
+const MOVED_VENDORED_GEMS: &str = "vendored_gems_path: vendor/gems";
+
+/// Adds `entries` to the end of the top-level list `key` in `yaml`, starting the list if the
+/// template doesn't have it. Entries are written as double-quoted scalars so globs starting
+/// with `*` or `{` stay strings.
+fn append_entries(yaml: &str, key: &str, entries: &[String]) -> String {
+    if entries.is_empty() {
+        return yaml.to_string();
+    }
+    let items = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "  - {}\n",
+                serde_json::to_string(entry).expect("strings always serialize")
+            )
+        })
+        .collect::<String>();
+    let header = format!("{}:", key);
+    let mut lines = yaml.lines().peekable();
+    let mut merged = String::new();
+    let mut found = false;
+    while let Some(line) = lines.next() {
+        merged.push_str(line);
+        merged.push('\n');
+        if line == header {
+            found = true;
+            while let Some(item) = lines.next_if(|next| next.starts_with("  ")) {
+                merged.push_str(item);
+                merged.push('\n');
+            }
+            merged.push_str(&items);
+        }
+    }
+    if !found {
+        merged.push_str(&header);
+        merged.push('\n');
+        merged.push_str(&items);
+    }
+    merged
+}
+
+const STANDARD: &str = "
+---
+owned_globs:
+  - \"{app,components,config,frontend,lib,packs,spec}/**/*.{rb,rake,js,jsx,ts,tsx,json,yml}\"
+unowned_globs:
+  - config/code_ownership.yml
+javascript_package_paths:
+  - javascript/packages/**
+vendored_gems_path: gems
+";
+
+const MINIMAL: &str = "---
+owned_globs:
+  - \"{app,packs}/**/*.rb\"
+";
+
+const EXTRA_KEYS: &str = "
+---
+owned_globs:
+  - \"{app,components,config,frontend,lib,packs,spec}/**/*.{rb,rake,js,jsx,ts,tsx,json,yml}\"
+unowned_globs:
+  - config/code_ownership.yml
+  - db/**/*
+javascript_package_paths:
+  - javascript/packages/**
+vendored_gems_path: gems
+ownership_dashboard_url: https://ownership.example.com
+legacy_codeowners_sections: false
+raw_cache_enabled: true
+cache_directory: tmp/cache/codeowners
+generated_by: random-rails-generator
+ownership_report:
+  slack_channel: '#ownership'
+  enabled: false
+";
+
+const ALTERNATE: &str = "---
+owned_globs:
+  - \"{app,lib,packs}/**/*.{rb,erb,rake}\"
+  - \"frontend/**/*.{js,ts,tsx}\"
+unowned_globs:
+  - \"**/vendor/**/*\"
+  - \"packs/**/spec/fixtures/**/*\"
+js_package_paths:
+  - frontend/packages/*
+vendored_gems_path: vendor/gems
+";
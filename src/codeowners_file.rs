@@ -0,0 +1,297 @@
+use std::{
+    fmt::{self, Write},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{Config, Manifest};
+
+/// Which ownership configuration the app is written with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OwnershipOutput {
+    /// `config/code_ownership.yml`, from which the code_ownership tools generate CODEOWNERS.
+    #[default]
+    CodeOwnership,
+    /// Hand-written CODEOWNERS files instead of `config/code_ownership.yml`, for tools that
+    /// read native CODEOWNERS, see [`Config::codeowners_files`]. Team YAML, `package.yml`
+    /// owners and annotations are still written, but nothing points the code_ownership tools
+    /// at them.
+    Codeowners,
+    /// Both. Tools that check CODEOWNERS is what they would generate will report it as stale.
+    Both,
+}
+
+impl FromStr for OwnershipOutput {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "code-ownership" => Ok(Self::CodeOwnership),
+            "codeowners" => Ok(Self::Codeowners),
+            "both" => Ok(Self::Both),
+            _ => anyhow::bail!(
+                "unknown ownership output '{}' (expected code-ownership, codeowners or both)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for OwnershipOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CodeOwnership => "code-ownership",
+            Self::Codeowners => "codeowners",
+            Self::Both => "both",
+        })
+    }
+}
+
+impl OwnershipOutput {
+    pub(crate) fn writes_code_ownership(self) -> bool {
+        self != Self::Codeowners
+    }
+
+    pub(crate) fn writes_codeowners(self) -> bool {
+        self != Self::CodeOwnership
+    }
+}
+
+/// The hosting service a CODEOWNERS file is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeownersFormat {
+    /// Comments between groups of rules; the last matching rule wins.
+    Github,
+    /// `[Section]` headers. GitLab combines the owners of every section a file matches, so
+    /// each file is only ever matched in one section.
+    Gitlab,
+    /// Bitbucket's Code Owners syntax, with teams as `@@group`.
+    Bitbucket,
+}
+
+impl FromStr for CodeownersFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(Self::Github),
+            "gitlab" => Ok(Self::Gitlab),
+            "bitbucket" => Ok(Self::Bitbucket),
+            _ => anyhow::bail!(
+                "unknown CODEOWNERS format '{}' (expected github, gitlab or bitbucket)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for CodeownersFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Github => "github",
+            Self::Gitlab => "gitlab",
+            Self::Bitbucket => "bitbucket",
+        })
+    }
+}
+
+impl CodeownersFormat {
+    /// Where the service looks for the file, among the locations it supports.
+    pub fn default_path(self) -> PathBuf {
+        PathBuf::from(match self {
+            Self::Github => ".github/CODEOWNERS",
+            Self::Gitlab => ".gitlab/CODEOWNERS",
+            Self::Bitbucket => ".bitbucket/CODEOWNERS",
+        })
+    }
+}
+
+/// One CODEOWNERS file to write, see [`Config::codeowners_files`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeownersFile {
+    pub format: CodeownersFormat,
+    /// Relative to the app root.
+    pub path: PathBuf,
+}
+
+impl FromStr for CodeownersFile {
+    type Err = anyhow::Error;
+
+    /// Parses `<format>` or `<format>=<path>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = match s.split_once('=') {
+            Some((format, path)) => (format.parse::<CodeownersFormat>()?, PathBuf::from(path)),
+            None => {
+                let format = s.parse::<CodeownersFormat>()?;
+                (format, format.default_path())
+            }
+        };
+        anyhow::ensure!(
+            path.components()
+                .all(|component| matches!(component, Component::Normal(_))),
+            "CODEOWNERS path '{}' must be relative to the app root",
+            path.display()
+        );
+        Ok(Self { format, path })
+    }
+}
+
+impl Default for CodeownersFile {
+    fn default() -> Self {
+        Self {
+            format: CodeownersFormat::Github,
+            path: CodeownersFormat::Github.default_path(),
+        }
+    }
+}
+
+/// A group of rules that never matches a file another group matches.
+struct Section {
+    title: String,
+    /// Patterns and the team owning them, or `None` for files nobody owns.
+    rules: Vec<(String, Option<String>)>,
+}
+
+/// Writes every configured CODEOWNERS file, or GitHub's when none are configured, giving each
+/// generated file the owner `manifest` says it has. Returns the number of files written.
+pub(crate) fn write(config: &Config, manifest: &Manifest) -> anyhow::Result<usize> {
+    let default = [CodeownersFile::default()];
+    let files = if config.codeowners_files.is_empty() {
+        &default[..]
+    } else {
+        &config.codeowners_files
+    };
+    let sections = sections(config, manifest);
+    for file in files {
+        let path = config.app_dir().join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, render(config, file.format, &sections)?)?;
+    }
+    Ok(files.len())
+}
+
+fn render(
+    config: &Config,
+    format: CodeownersFormat,
+    sections: &[Section],
+) -> anyhow::Result<String> {
+    let mut contents =
+        String::from("# Written by random-rails-generator to match generator-manifest.json.\n");
+    for section in sections {
+        match format {
+            CodeownersFormat::Gitlab => writeln!(contents, "\n[{}]", section.title)?,
+            CodeownersFormat::Github | CodeownersFormat::Bitbucket => {
+                writeln!(contents, "\n# {}", section.title)?
+            }
+        }
+        for (pattern, team) in &section.rules {
+            writeln!(
+                contents,
+                "{}",
+                rule(config, format, pattern, team.as_deref())
+            )?;
+        }
+    }
+    Ok(contents)
+}
+
+/// The rules of a `format` file for `manifest`, in order, without comments or section headers.
+pub(crate) fn rule_lines(
+    config: &Config,
+    manifest: &Manifest,
+    format: CodeownersFormat,
+) -> Vec<String> {
+    sections(config, manifest)
+        .iter()
+        .flat_map(|section| &section.rules)
+        .map(|(pattern, team)| rule(config, format, pattern, team.as_deref()))
+        .collect()
+}
+
+fn rule(config: &Config, format: CodeownersFormat, pattern: &str, team: Option<&str>) -> String {
+    match (team, format) {
+        (Some(team), _) => format!("{} {}", pattern, handle(config, format, team)),
+        // Without owners the entry would take the section's default owners.
+        (None, CodeownersFormat::Gitlab) => format!("!{}", pattern),
+        (None, _) => pattern.to_string(),
+    }
+}
+
+/// How `format` names `team`. The infra team keeps the GitHub team it was configured with.
+fn handle(config: &Config, format: CodeownersFormat, team: &str) -> String {
+    match format {
+        CodeownersFormat::Bitbucket => format!("@@{}", team),
+        _ if team == config.infra_team.name => config.infra_team.github.team.clone(),
+        _ => format!("@{}", team),
+    }
+}
+
+/// The rules for `manifest`, grouped so no file is matched in two sections: the infra team's
+/// globs, the app's own code, team spec support, then each pack with its exceptions after it.
+fn sections(config: &Config, manifest: &Manifest) -> Vec<Section> {
+    let mut sections = vec![
+        Section {
+            title: "Infra team".to_string(),
+            rules: config
+                .infra_team
+                .owned_globs(config)
+                .iter()
+                .map(|glob| (format!("/{}", glob), Some(config.infra_team.name.clone())))
+                .collect(),
+        },
+        Section {
+            title: "App code outside packs".to_string(),
+            rules: manifest
+                .app_code
+                .iter()
+                .map(|dir| (pattern(&dir.path, true), Some(dir.team.clone())))
+                .collect(),
+        },
+        Section {
+            title: "Team spec support".to_string(),
+            rules: manifest
+                .spec_support
+                .iter()
+                .filter(|file| file.team != config.infra_team.name)
+                .map(|file| (pattern(&file.path, false), Some(file.team.clone())))
+                .collect(),
+        },
+    ];
+    for pack in &manifest.packs {
+        let Some(team) = &pack.team else {
+            continue;
+        };
+        let mut rules = vec![(pattern(&pack.path, true), Some(team.clone()))];
+        if let Some(co_owner) = &pack.co_owner {
+            for directory in &co_owner.directories {
+                rules.push((pattern(directory, true), Some(co_owner.team.clone())));
+            }
+        }
+        for nested in &pack.nested_codeowners {
+            rules.push((pattern(&nested.directory, true), Some(nested.team.clone())));
+        }
+        // Files outside the pack need a rule even when its team owns them.
+        for (path, file_team) in pack.listed_files() {
+            if file_team.as_ref() != Some(team) || !path.starts_with(&pack.path) {
+                rules.push((pattern(path, false), file_team));
+            }
+        }
+        sections.push(Section {
+            title: pack.path.display().to_string(),
+            rules,
+        });
+    }
+    sections.retain(|section| !section.rules.is_empty());
+    sections
+}
+
+/// `path` anchored at the repository root, with spaces escaped.
+fn pattern(path: &Path, directory: bool) -> String {
+    format!(
+        "/{}{}",
+        path.to_string_lossy().replace(' ', "\\ "),
+        if directory { "/" } else { "" }
+    )
+}
@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path, str::FromStr};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::Downloads;
+
+/// Archive formats dotslash can unpack, matched against the end of an artifact URL.
+const ARCHIVE_FORMATS: &[&str] = &[
+    "tar.gz", "tar.zst", "tar.xz", "tar", "zip", "gz", "zst", "xz",
+];
+
+/// One platform's release artifact referenced from a generated dotslash descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotslashArtifact {
+    /// Dotslash platform key, e.g. `linux-x86_64` or `macos-aarch64`.
+    pub platform: String,
+    pub url: String,
+    /// Path of the executable inside the archive. Defaults to the tool's binary name.
+    pub path: Option<String>,
+}
+
+impl FromStr for DotslashArtifact {
+    type Err = anyhow::Error;
+
+    /// Parses `<platform>=<url>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((platform, url)) = s.split_once('=') else {
+            anyhow::bail!("expected <platform>=<url>, got '{}'", s);
+        };
+        Ok(Self {
+            platform: platform.to_string(),
+            url: url.to_string(),
+            path: None,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct Descriptor<'a> {
+    name: &'a str,
+    platforms: BTreeMap<&'a str, PlatformEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct PlatformEntry<'a> {
+    size: usize,
+    hash: &'static str,
+    digest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
+    path: &'a str,
+    providers: Vec<Provider<'a>>,
+}
+
+#[derive(Serialize)]
+struct Provider<'a> {
+    url: &'a str,
+}
+
+/// Writes a dotslash descriptor for `name` to `path`. Every artifact is downloaded once so its
+/// size and sha256 digest can be recorded, which is what lets dotslash fetch and verify the
+/// right binary on each teammate's platform.
+pub(crate) fn write_descriptor(
+    downloads: &Downloads,
+    path: &Path,
+    name: &str,
+    artifacts: &[DotslashArtifact],
+) -> anyhow::Result<()> {
+    let mut platforms = BTreeMap::new();
+    for artifact in artifacts {
+        let bytes = downloads.fetch(&artifact.url)?;
+        platforms.insert(
+            artifact.platform.as_str(),
+            PlatformEntry {
+                size: bytes.len(),
+                hash: "sha256",
+                digest: hex_digest(&bytes),
+                format: archive_format(&artifact.url),
+                path: artifact.path.as_deref().unwrap_or(name),
+                providers: vec![Provider { url: &artifact.url }],
+            },
+        );
+    }
+
+    let descriptor = serde_json::to_string_pretty(&Descriptor { name, platforms })?;
+    std::fs::write(path, format!("#!/usr/bin/env dotslash\n\n{}\n", descriptor))?;
+    crate::make_executable(path)
+}
+
+fn archive_format(url: &str) -> Option<&'static str> {
+    ARCHIVE_FORMATS
+        .iter()
+        .find(|format| url.ends_with(&format!(".{}", format)))
+        .copied()
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+}
@@ -0,0 +1,322 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use reqwest::{
+    blocking::{Client, Response},
+    header::HeaderMap,
+};
+use serde::Deserialize;
+
+use crate::{Config, ToolManifest};
+
+const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Builds the client used for every download. Proxies come from the standard `HTTPS_PROXY`,
+/// `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` environment variables; certificates from
+/// [`Config::ca_bundle`] are trusted in addition to the system roots.
+pub(crate) fn http_client(config: &Config) -> anyhow::Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent("random-rails-generator")
+        .danger_accept_invalid_certs(config.insecure_tls);
+    if let Some(ca_bundle) = &config.ca_bundle {
+        let pem = std::fs::read(ca_bundle)
+            .with_context(|| format!("failed to read CA bundle {}", ca_bundle))?;
+        for certificate in reqwest::Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// The HTTP client and everything downloaded with it, shared by the apps of one run so a
+/// matrix resolves and fetches each tool once however many apps it builds.
+pub(crate) struct Downloads {
+    pub client: Client,
+    fetched: Mutex<HashMap<String, Arc<Vec<u8>>>>,
+    resolved: Mutex<HashMap<(String, Option<String>), ToolManifest>>,
+}
+
+impl Downloads {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self {
+            client: http_client(config)?,
+            fetched: Mutex::default(),
+            resolved: Mutex::default(),
+        })
+    }
+
+    /// [`fetch`], downloading each URL only once. The lock is held while downloading so apps
+    /// built at the same time wait for the first download instead of repeating it.
+    pub fn fetch(&self, url: &str) -> anyhow::Result<Arc<Vec<u8>>> {
+        let mut fetched = self.fetched.lock().expect("download cache poisoned");
+        if let Some(bytes) = fetched.get(url) {
+            return Ok(bytes.clone());
+        }
+        let bytes = Arc::new(fetch(&self.client, url)?);
+        fetched.insert(url.to_string(), bytes.clone());
+        Ok(bytes)
+    }
+
+    /// Resolves pks and codeowners-rs as configured, see [`resolve_tool`].
+    pub fn resolve_tools(&self, config: &Config) -> anyhow::Result<(ToolManifest, ToolManifest)> {
+        let pks = self.resolve(
+            &PKS,
+            &config.pks_dotslash_path,
+            config.pks_version.as_deref(),
+        )?;
+        let codeowners = self.resolve(
+            &CODEOWNERS,
+            &config.codeowners_dotslash_path,
+            config.codeowners_version.as_deref(),
+        )?;
+        Ok((pks, codeowners))
+    }
+
+    /// [`resolve_tool`], asking GitHub about each source and version only once.
+    fn resolve(
+        &self,
+        source: &ReleaseSource,
+        url: &str,
+        version: Option<&str>,
+    ) -> anyhow::Result<ToolManifest> {
+        let mut resolved = self.resolved.lock().expect("download cache poisoned");
+        let key = (
+            format!("{} {}", source.repo, url),
+            version.map(str::to_string),
+        );
+        if let Some(tool) = resolved.get(&key) {
+            return Ok(tool.clone());
+        }
+        let tool = resolve_tool(&self.client, source, url, version)?;
+        resolved.insert(key, tool.clone());
+        Ok(tool)
+    }
+}
+
+/// Downloads `url`, treating HTTP error statuses as failures rather than saving the error page.
+pub(crate) fn fetch(client: &Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    let response = client
+        .get(url)
+        .send()
+        .with_context(|| format!("failed to download {}", url))?;
+    Ok(response.error_for_status()?.bytes()?.to_vec())
+}
+
+/// Where a tool's releases are published on GitHub.
+pub(crate) struct ReleaseSource {
+    pub name: &'static str,
+    pub repo: &'static str,
+    /// Name of the release asset holding the tool.
+    pub asset: &'static str,
+}
+
+pub(crate) const PKS: ReleaseSource = ReleaseSource {
+    name: "pks",
+    repo: "rubyatscale/pks",
+    asset: "pks",
+};
+
+pub(crate) const CODEOWNERS: ReleaseSource = ReleaseSource {
+    name: "codeowners",
+    repo: "rubyatscale/codeowners-rs",
+    asset: "codeowners",
+};
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    draft: bool,
+    prerelease: bool,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Picks the download URL for `source`. Without a `version` the pinned `url` is used as is;
+/// otherwise `version` is resolved against the repository's GitHub releases: `latest`, an
+/// exact tag, or a tag pattern with `*` wildcards such as `v0.2.*`. The newest matching
+/// release that isn't a draft or prerelease wins.
+pub(crate) fn resolve_tool(
+    client: &Client,
+    source: &ReleaseSource,
+    url: &str,
+    version: Option<&str>,
+) -> anyhow::Result<ToolManifest> {
+    let Some(version) = version else {
+        return Ok(ToolManifest {
+            name: source.name.to_string(),
+            version: None,
+            url: url.to_string(),
+        });
+    };
+
+    let release = find_release(client, source, version)?
+        .ok_or_else(|| anyhow::anyhow!("no {} release matches '{}'", source.repo, version))?;
+    let asset = release
+        .assets
+        .into_iter()
+        .find(|asset| asset.name == source.asset)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} release {} has no '{}' asset",
+                source.repo,
+                release.tag_name,
+                source.asset
+            )
+        })?;
+
+    Ok(ToolManifest {
+        name: source.name.to_string(),
+        version: Some(release.tag_name),
+        url: asset.browser_download_url,
+    })
+}
+
+/// The newest release matching `version`. `latest` asks GitHub for it directly; anything else
+/// walks the release list a page at a time until a tag matches.
+fn find_release(
+    client: &Client,
+    source: &ReleaseSource,
+    version: &str,
+) -> anyhow::Result<Option<Release>> {
+    let api_url =
+        std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_GITHUB_API_URL.to_string());
+    if version == "latest" {
+        let response = github_get(
+            client,
+            &format!("{}/repos/{}/releases/latest", api_url, source.repo),
+        )?;
+        // GitHub answers 404 when the repository has no published release.
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        return Ok(Some(serde_json::from_str(
+            &response.error_for_status()?.text()?,
+        )?));
+    }
+    let mut next = Some(format!(
+        "{}/repos/{}/releases?per_page=100",
+        api_url, source.repo
+    ));
+    while let Some(url) = next {
+        let response = github_get(client, &url)?.error_for_status()?;
+        next = next_page(response.headers());
+        let releases: Vec<Release> = serde_json::from_str(&response.text()?)?;
+        if let Some(release) = releases.into_iter().find(|release| {
+            !release.draft && !release.prerelease && matches_pattern(version, &release.tag_name)
+        }) {
+            return Ok(Some(release));
+        }
+    }
+    Ok(None)
+}
+
+/// Honors `GITHUB_API_URL` (set by GitHub Actions and useful for GitHub Enterprise) and
+/// authenticates with `GITHUB_TOKEN` when present to avoid the anonymous rate limit.
+fn github_get(client: &Client, url: &str) -> anyhow::Result<Response> {
+    let mut request = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+    Ok(request.send()?)
+}
+
+/// The `rel="next"` URL of a paginated GitHub response's `Link` header.
+fn next_page(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(reqwest::header::LINK)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .find_map(|link| {
+            let (url, params) = link.split_once(';')?;
+            params
+                .split(';')
+                .any(|param| param.trim() == "rel=\"next\"")
+                .then(|| {
+                    url.trim()
+                        .trim_start_matches('<')
+                        .trim_end_matches('>')
+                        .to_string()
+                })
+        })
+}
+
+/// Glob-style match where `*` matches any run of characters.
+fn matches_pattern(pattern: &str, tag: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = tag.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderValue, LINK};
+
+    use super::*;
+
+    #[test]
+    fn patterns_match_whole_tags() {
+        assert!(matches_pattern("v0.2.23", "v0.2.23"));
+        assert!(matches_pattern("v0.2.*", "v0.2.23"));
+        assert!(matches_pattern("v*.23", "v0.2.23"));
+        assert!(matches_pattern("*", "v0.2.23"));
+        assert!(!matches_pattern("v0.2.*", "v0.3.0"));
+        assert!(!matches_pattern("v0.2", "v0.2.23"));
+        assert!(!matches_pattern("0.2.*", "v0.2.23"));
+    }
+
+    #[test]
+    fn stars_need_room_for_what_follows_them() {
+        assert!(matches_pattern("v*-rc*", "v1.0-rc1"));
+        assert!(!matches_pattern("v*-rc*", "v1.0"));
+        assert!(!matches_pattern("a*a", "a"));
+        assert!(!matches_pattern("a*bc*c", "abc"));
+    }
+
+    fn headers(link: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_str(link).unwrap());
+        headers
+    }
+
+    #[test]
+    fn next_page_follows_the_next_link() {
+        let link = "<https://api.github.com/releases?page=2>; rel=\"next\", \
+                    <https://api.github.com/releases?page=5>; rel=\"last\"";
+        assert_eq!(
+            next_page(&headers(link)).as_deref(),
+            Some("https://api.github.com/releases?page=2")
+        );
+    }
+
+    #[test]
+    fn last_page_has_no_next_link() {
+        let link = "<https://api.github.com/releases?page=4>; rel=\"prev\", \
+                    <https://api.github.com/releases?page=1>; rel=\"first\"";
+        assert_eq!(next_page(&headers(link)), None);
+        assert_eq!(next_page(&HeaderMap::new()), None);
+    }
+}
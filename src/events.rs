@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{
+    AnnotationEdgeCase, CaseTwin, MalformedFile, Manifest, OddFile, SyntaxErrorFile, ToolRun,
+};
+
+/// A coarse step of app generation, in the order [`crate::build_app`] runs them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    RailsApp,
+    DotslashTools,
+    InfraTeam,
+    Packs,
+    AppCode,
+    SpecSupport,
+    Noise,
+    Codeowners,
+    ToolRuns,
+    /// Only when [`crate::Config::post_generation_hooks`] are set.
+    Hooks,
+}
+
+impl Phase {
+    /// The name the phase serializes as.
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::RailsApp => "rails_app",
+            Phase::DotslashTools => "dotslash_tools",
+            Phase::InfraTeam => "infra_team",
+            Phase::Packs => "packs",
+            Phase::AppCode => "app_code",
+            Phase::SpecSupport => "spec_support",
+            Phase::Noise => "noise",
+            Phase::Codeowners => "codeowners",
+            Phase::ToolRuns => "tool_runs",
+            Phase::Hooks => "hooks",
+        }
+    }
+}
+
+/// Progress reported to the observer passed to [`crate::build_app_with_observer`].
+///
+/// Serializes as `{"event": "<snake_case variant>", "data": ...}`, one object per event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum GenerationEvent {
+    /// A matrix run is starting on its next app. The events that follow belong to it, unless
+    /// apps are built concurrently, when its events come wrapped in [`GenerationEvent::App`].
+    AppStarted {
+        app_name: String,
+        rails: String,
+    },
+    /// An event of one of the apps a concurrent matrix run is building, see
+    /// [`crate::Config::jobs`].
+    App {
+        app_name: String,
+        event: Box<GenerationEvent>,
+    },
+    PhaseStarted(Phase),
+    PhaseFinished(Phase),
+    /// Generation is continuing from a checkpoint; the setup phases and all but the last of the
+    /// first `completed_packs` packs are not run again.
+    Resumed {
+        completed_packs: usize,
+        total: usize,
+    },
+    /// A pack and its team were written. `index` is zero-based out of `total`.
+    PackCompleted {
+        name: String,
+        index: usize,
+        total: usize,
+        files_written: usize,
+    },
+    /// The pack's team directory already existed, so nothing was written for it.
+    PackSkipped {
+        name: String,
+        index: usize,
+        total: usize,
+    },
+    /// One of the tools run against the finished app, see [`crate::Config::run_tools`].
+    ToolFinished(ToolRun),
+    /// Every phase finished. `files_written` is the total files written; `faults` lists what
+    /// the app breaks on purpose.
+    Finished {
+        files_written: usize,
+        faults: InjectedFaults,
+    },
+}
+
+/// Every file the app breaks or complicates on purpose, as listed in its manifest, so a
+/// consumer of the event stream needn't read the manifest to know what tools should report.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InjectedFaults {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotation_edge_cases: Vec<AnnotationEdgeCase>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub odd_files: Vec<OddFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub long_path_files: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub case_twins: Vec<CaseTwin>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub malformed_files: Vec<MalformedFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub syntax_error_files: Vec<SyntaxErrorFile>,
+}
+
+impl InjectedFaults {
+    pub(crate) fn new(manifest: &Manifest) -> Self {
+        let mut faults = Self::default();
+        for pack in &manifest.packs {
+            faults
+                .annotation_edge_cases
+                .extend(pack.annotation_edge_cases.iter().cloned());
+            faults.odd_files.extend(pack.odd_files.iter().cloned());
+            faults
+                .long_path_files
+                .extend(pack.long_path_file.iter().cloned());
+            faults.case_twins.extend(pack.case_twins.iter().cloned());
+            faults
+                .malformed_files
+                .extend(pack.malformed_file.iter().cloned());
+            faults
+                .syntax_error_files
+                .extend(pack.syntax_error_files.iter().cloned());
+        }
+        faults
+    }
+
+    /// The number of faulty files.
+    pub fn len(&self) -> usize {
+        self.annotation_edge_cases.len()
+            + self.odd_files.len()
+            + self.long_path_files.len()
+            + self.case_twins.len()
+            + self.malformed_files.len()
+            + self.syntax_error_files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl GenerationEvent {
+    /// The event itself, unwrapped from any [`GenerationEvent::App`].
+    pub fn inner(&self) -> &GenerationEvent {
+        match self {
+            GenerationEvent::App { event, .. } => event.inner(),
+            event => event,
+        }
+    }
+}
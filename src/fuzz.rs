@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::{
+    build_app_sharing, tools, CodeOwnershipShape, Config, Downloads, GenerationEvent, Manifest,
+    OnExists, PackNamespaces, PackageMetadata, PackageOwnerKey, PackageRoot, RailsFlavor,
+    TeamLayout, TeamSize, ToolOutcome,
+};
+
+/// Salt for sampling a case's options, so they don't correlate with the packs generated from
+/// the same seed.
+const OPTIONS_SEED_SALT: u64 = 0x6675_7A7A_6F70_7473;
+
+/// What to explore, see [`run_fuzz`].
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    /// Where the tools come from and where apps are written. Every option that shapes the app
+    /// is replaced by the sampled one.
+    pub template: Config,
+    pub iterations: usize,
+    /// Seeds the sequence of case seeds. A random seed is used when unset.
+    pub seed: Option<u64>,
+    /// Upper bound on the packs in a sampled app. Small apps keep cases fast and findings easy
+    /// to read.
+    pub max_packages: usize,
+    /// Keep the apps of cases without findings. Apps with findings are always kept.
+    pub keep_apps: bool,
+}
+
+/// The result of one sampled app. Rerunning with [`run_case`] and the same `seed` generates
+/// the same app.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FuzzCase {
+    pub seed: u64,
+    pub app_dir: PathBuf,
+    pub findings: Vec<Finding>,
+}
+
+/// Something a tool got wrong about a well-formed app.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    /// The tool could not be started, panicked, or was killed by a signal.
+    ToolCrashed { command: String, reason: String },
+    /// The tool reported problems with an app that has none.
+    ToolFailed {
+        command: String,
+        exit_code: i32,
+        stderr: String,
+    },
+    /// The tool named a different owner than the manifest records. `None` is unowned.
+    OwnershipMismatch {
+        path: PathBuf,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+}
+
+impl Finding {
+    fn from_outcome(command: String, outcome: ToolOutcome) -> Option<Self> {
+        match outcome {
+            ToolOutcome::Passed => None,
+            ToolOutcome::Failed { exit_code, stderr } => Some(Self::ToolFailed {
+                command,
+                exit_code,
+                stderr,
+            }),
+            ToolOutcome::Crashed { reason } => Some(Self::ToolCrashed { command, reason }),
+        }
+    }
+}
+
+/// Generates `config.iterations` apps with randomly sampled options, runs the tools against
+/// each, and reports every case to `on_case` as it finishes. Stops early with
+/// [`crate::Cancelled`] when the template's cancellation token fires.
+pub fn run_fuzz(config: &FuzzConfig, mut on_case: impl FnMut(&FuzzCase)) -> anyhow::Result<()> {
+    let mut seeds = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+    // Every case runs the same tools, so they're downloaded once for all of them.
+    let downloads = Downloads::new(&config.template)?;
+    for _ in 0..config.iterations {
+        let case = check_case(config, seeds.gen(), &downloads)?;
+        if case.findings.is_empty() && !config.keep_apps {
+            std::fs::remove_dir_all(&case.app_dir)?;
+        }
+        on_case(&case);
+    }
+    Ok(())
+}
+
+/// Generates and checks the single app for `seed`, keeping it on disk.
+pub fn run_case(config: &FuzzConfig, seed: u64) -> anyhow::Result<FuzzCase> {
+    check_case(config, seed, &Downloads::new(&config.template)?)
+}
+
+fn check_case(config: &FuzzConfig, seed: u64, downloads: &Downloads) -> anyhow::Result<FuzzCase> {
+    let app_config = sample_config(config, seed);
+    let app_dir = app_config.app_dir();
+    let mut findings = Vec::new();
+    build_app_sharing(app_config, Some(downloads), |event| {
+        if let GenerationEvent::ToolFinished(run) = event {
+            findings.extend(Finding::from_outcome(
+                run.command.clone(),
+                run.outcome.clone(),
+            ));
+        }
+    })?;
+    findings.extend(check_owners(&app_dir)?);
+    Ok(FuzzCase {
+        seed,
+        app_dir,
+        findings,
+    })
+}
+
+/// The app for `seed`: every option that should leave a valid app is drawn at random.
+/// Pathological annotations, malformed files and syntax errors are left out because tools are
+/// expected to report them, and long paths because they depend on where the app is written.
+fn sample_config(config: &FuzzConfig, seed: u64) -> Config {
+    let mut rng = StdRng::seed_from_u64(seed ^ OPTIONS_SEED_SALT);
+    Config {
+        app_name: format!("fuzz-{}", seed),
+        num_packages: rng.gen_range(1..=config.max_packages.max(1)),
+        seed: Some(seed),
+        resume: false,
+        on_exists: OnExists::Overwrite,
+        run_tools: true,
+        embedded_skeleton: true,
+        rails_flavor: match rng.gen_range(0..4) {
+            0 => RailsFlavor::Rails61,
+            1 => RailsFlavor::Rails70,
+            2 => RailsFlavor::Rails72,
+            _ => RailsFlavor::Rails80,
+        },
+        matrix: Vec::new(),
+        co_owned_percent: percent(&mut rng),
+        ownerless_percent: percent(&mut rng),
+        team_rename_percent: percent(&mut rng),
+        team_size: match rng.gen_range(0..3) {
+            0 => TeamSize::Fixed(rng.gen_range(1..=3)),
+            1 => TeamSize::Uniform { min: 1, max: 8 },
+            _ => TeamSize::LongTail { min: 1, max: 50 },
+        },
+        team_layout: match rng.gen_range(0..3) {
+            0 => TeamLayout::Nested,
+            1 => TeamLayout::Flat,
+            _ => TeamLayout::Combined,
+        },
+        code_ownership_shape: CodeOwnershipShape::Random,
+        extra_owned_globs: Vec::new(),
+        extra_unowned_globs: Vec::new(),
+        varied_annotations: rng.gen_bool(0.5),
+        pathological_annotations: false,
+        odd_files_percent: percent(&mut rng),
+        long_paths_percent: 0,
+        case_twins_percent: percent(&mut rng),
+        malformed_percent: 0,
+        syntax_error_percent: 0,
+        pack_docs: rng.gen_bool(0.5),
+        app_code_dirs: rng.gen_range(0..=3),
+        spec_support: rng.gen_bool(0.5),
+        package_metadata: if rng.gen_bool(0.5) {
+            vec![
+                PackageMetadata::Protections,
+                PackageMetadata::CustomKeys,
+                PackageMetadata::ProductGroup,
+            ]
+        } else {
+            Vec::new()
+        },
+        package_owner_key: PackageOwnerKey::Random,
+        nested_codeowners_percent: percent(&mut rng),
+        package_roots: if rng.gen_bool(0.5) {
+            vec![
+                PackageRoot::Packs,
+                PackageRoot::Components,
+                PackageRoot::Gems,
+                PackageRoot::Domains,
+            ]
+        } else {
+            Vec::new()
+        },
+        pack_namespaces: [
+            PackNamespaces::Flat,
+            PackNamespaces::Prefixed,
+            PackNamespaces::Nested,
+        ][rng.gen_range(0..3)],
+        fixture_export: false,
+        preset: None,
+        ..config.template.clone()
+    }
+}
+
+/// Half the time off, otherwise anywhere from a single pack to all of them.
+fn percent(rng: &mut StdRng) -> u8 {
+    if rng.gen_bool(0.5) {
+        0
+    } else {
+        rng.gen_range(1..=100)
+    }
+}
+
+/// Asks the tool for the owner of a sample of files whose owner the manifest records: the
+/// first code file of each pack, of each co-owned part and of each directory of app code, and
+/// every odd file, case twin and spec support file.
+fn check_owners(app_dir: &Path) -> anyhow::Result<Vec<Finding>> {
+    let manifest = Manifest::load(app_dir)?;
+    // Keyed by path, since a pack's first file can also be one of its case twins.
+    let mut expected = BTreeMap::new();
+    for pack in &manifest.packs {
+        let services = pack.path.join("app/services/a");
+        let services_team = match &pack.co_owner {
+            Some(co_owner) if co_owner.directories.contains(&services) => {
+                Some(co_owner.team.clone())
+            }
+            _ => pack.team.clone(),
+        };
+        if let Some(path) = first_ruby_file(app_dir, &services)? {
+            expected.insert(path, services_team);
+        }
+        if let Some(co_owner) = &pack.co_owner {
+            for dir in co_owner.directories.iter().take(1) {
+                if let Some(path) = first_ruby_file(app_dir, dir)? {
+                    expected.insert(path, Some(co_owner.team.clone()));
+                }
+            }
+        }
+        for nested in &pack.nested_codeowners {
+            if let Some(path) = first_ruby_file(app_dir, &nested.directory)? {
+                expected.insert(path, Some(nested.team.clone()));
+            }
+        }
+        for odd_file in &pack.odd_files {
+            expected.insert(odd_file.path.clone(), odd_file.team.clone());
+        }
+        for twin in &pack.case_twins {
+            expected.insert(twin.path.clone(), twin.team.clone());
+        }
+    }
+
+    for dir in &manifest.app_code {
+        if let Some(path) = first_ruby_file(app_dir, &dir.path)? {
+            expected.insert(path, Some(dir.team.clone()));
+        }
+    }
+    for file in &manifest.spec_support {
+        expected.insert(file.path.clone(), Some(file.team.clone()));
+    }
+
+    let mut findings = Vec::new();
+    for (path, expected) in expected {
+        match tools::file_owner(app_dir, &path) {
+            Ok(actual) if actual == expected => {}
+            Ok(actual) => findings.push(Finding::OwnershipMismatch {
+                path,
+                expected,
+                actual,
+            }),
+            Err(run) => findings.extend(Finding::from_outcome(run.command, run.outcome)),
+        }
+    }
+    Ok(findings)
+}
+
+/// The alphabetically first `.rb` file directly in `dir`, relative to the app.
+fn first_ruby_file(app_dir: &Path, dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let full_dir = app_dir.join(dir);
+    if !full_dir.is_dir() {
+        return Ok(None);
+    }
+    let mut files = std::fs::read_dir(&full_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    files.sort();
+    Ok(files
+        .into_iter()
+        .find(|name| Path::new(name).extension().is_some_and(|ext| ext == "rb"))
+        .map(|name| dir.join(name)))
+}
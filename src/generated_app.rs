@@ -0,0 +1,86 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::{
+    codeowners_file,
+    ownership_table::{self, Owners, Row},
+    CodeownersFormat, Config, Manifest, PackManifest, PackOwnership,
+};
+
+/// An app [`crate::build_app`] finished, with what it was generated to contain, for test
+/// harnesses to assert against without walking the app themselves.
+#[derive(Debug, Clone)]
+pub struct GeneratedApp {
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+    config: Config,
+    /// Every file in the app with its intended owner, found on first use.
+    files: OnceLock<Vec<Row>>,
+}
+
+impl GeneratedApp {
+    pub(crate) fn new(config: &Config, manifest: Manifest) -> Self {
+        Self {
+            dir: config.app_dir(),
+            manifest,
+            config: config.clone(),
+            files: OnceLock::new(),
+        }
+    }
+
+    /// An app a previous run finished, loaded from its manifest.
+    pub(crate) fn load(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self::new(config, Manifest::load(&config.app_dir())?))
+    }
+
+    /// Every file in the app with its intended owner. The app is walked the first time only.
+    pub(crate) fn files(&self) -> anyhow::Result<&[Row]> {
+        if let Some(files) = self.files.get() {
+            return Ok(files);
+        }
+        let files = ownership_table::rows(&self.dir, &self.config, &self.manifest)?;
+        Ok(self.files.get_or_init(|| files))
+    }
+
+    /// The files `team` owns, relative to [`Self::dir`], in the order they were found. Files
+    /// are owned as the generator intended, even where the configured globs leave tools blind
+    /// to them. The first call walks the app.
+    pub fn files_owned_by(&self, team: &str) -> anyhow::Result<Vec<&Path>> {
+        Ok(self
+            .files()?
+            .iter()
+            .filter(|file| file.team.as_deref() == Some(team))
+            .map(|file| file.path.as_path())
+            .collect())
+    }
+
+    /// The team owning `path`, relative to [`Self::dir`], or `None` when nobody does or the
+    /// file isn't part of the app.
+    pub fn owner_of(&self, path: &Path) -> Option<String> {
+        if !self.dir.join(path).is_file() {
+            return None;
+        }
+        Owners::new(&self.config, &self.manifest).owner(path).0
+    }
+
+    pub fn packs_with_ownership(&self, ownership: PackOwnership) -> Vec<&PackManifest> {
+        self.manifest
+            .packs
+            .iter()
+            .filter(|pack| pack.ownership == ownership)
+            .collect()
+    }
+
+    /// The rules a GitHub CODEOWNERS file for the app holds, in order, without comments. They
+    /// are written to the app when [`Config::ownership_output`] asks for CODEOWNERS.
+    pub fn expected_codeowners_lines(&self) -> Vec<String> {
+        self.expected_codeowners_lines_for(CodeownersFormat::Github)
+    }
+
+    /// [`Self::expected_codeowners_lines`] in another service's syntax.
+    pub fn expected_codeowners_lines_for(&self, format: CodeownersFormat) -> Vec<String> {
+        codeowners_file::rule_lines(&self.config, &self.manifest, format)
+    }
+}
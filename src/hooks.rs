@@ -0,0 +1,33 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{Config, Manifest};
+
+/// Runs each of [`Config::post_generation_hooks`] with `sh -c` in the finished app, stopping
+/// at the first that fails. Hooks get the absolute app directory, manifest path and seed in
+/// `RANDOM_RAILS_GENERATOR_APP_DIR`, `RANDOM_RAILS_GENERATOR_MANIFEST` and
+/// `RANDOM_RAILS_GENERATOR_SEED`. Their output goes to stderr, leaving stdout to progress.
+pub(crate) fn run(config: &Config, app_dir: &Path, seed: u64) -> anyhow::Result<()> {
+    let app_dir = std::path::absolute(app_dir)?;
+    for hook in &config.post_generation_hooks {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .current_dir(&app_dir)
+            .env("RANDOM_RAILS_GENERATOR_APP_DIR", &app_dir)
+            .env("RANDOM_RAILS_GENERATOR_MANIFEST", Manifest::path(&app_dir))
+            .env("RANDOM_RAILS_GENERATOR_SEED", seed.to_string())
+            .stdin(Stdio::null())
+            .stdout(std::io::stderr())
+            .status()?;
+        anyhow::ensure!(
+            status.success(),
+            "post-generation hook `{}` failed: {}",
+            hook,
+            status
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{app_code, spec_support, Config};
+
+/// What the infra team owns by default: everything under `app` outside the packs, and the
+/// configuration files of a new Rails app.
+const DEFAULT_OWNED_GLOBS: &[&str] = &[
+    "app/**",
+    "config/application.rb",
+    "config/boot.rb",
+    "config/cable.yml",
+    "config/database.yml",
+    "config/environment.rb",
+    "config/environments/development.rb",
+    "config/environments/production.rb",
+    "config/environments/test.rb",
+    "config/importmap.rb",
+    "config/initializers/assets.rb",
+    "config/initializers/content_security_policy.rb",
+    "config/initializers/filter_parameter_logging.rb",
+    "config/initializers/inflections.rb",
+    "config/initializers/permissions_policy.rb",
+    "config/locales/en.yml",
+    "config/puma.rb",
+    "config/routes.rb",
+    "config/storage.yml",
+    "config/cache.yml",
+    "config/deploy.yml",
+    "config/queue.yml",
+    "config/recurring.yml",
+];
+
+/// The glob [`Config::app_code_dirs`] replaces with [`app_code::INFRA_APP_GLOBS`].
+const APP_GLOB: &str = "app/**";
+
+/// The team that owns the app outside its packs, written as `infra` in the team layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InfraTeam {
+    pub name: String,
+    pub github: GithubTeam,
+    #[serde(default)]
+    pub owned_globs: Vec<String>,
+    /// Any other keys from a loaded team file, written back unchanged.
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GithubTeam {
+    pub team: String,
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl Default for InfraTeam {
+    fn default() -> Self {
+        Self::named("devops")
+    }
+}
+
+impl InfraTeam {
+    /// The default team under another name.
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            github: GithubTeam {
+                team: format!("@{}", name),
+                members: vec![format!("{} member", name)],
+            },
+            owned_globs: DEFAULT_OWNED_GLOBS
+                .iter()
+                .map(|glob| glob.to_string())
+                .collect(),
+            extra: serde_yaml::Mapping::new(),
+        }
+    }
+
+    /// Reads a team definition in the usual team YAML format, e.g. a real app's platform team.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("can't read infra team {}", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("{} is not a valid team definition", path.display()))
+    }
+
+    /// The team's YAML for `config`, with [`Self::owned_globs`] in place of its own. The result
+    /// is parsed back before it's returned, so nothing invalid gets written.
+    pub(crate) fn render(&self, config: &Config) -> anyhow::Result<String> {
+        let team = Self {
+            owned_globs: self.owned_globs(config),
+            ..self.clone()
+        };
+        let yaml = serde_yaml::to_string(&team)?;
+        serde_yaml::from_str::<Self>(&yaml)
+            .with_context(|| format!("infra team {} renders as invalid YAML", self.name))?;
+        Ok(yaml)
+    }
+
+    /// What the team owns in an app generated with `config`: with app code, `app/**` narrows
+    /// to the skeleton's files, and with spec support the shared spec files are added.
+    pub(crate) fn owned_globs(&self, config: &Config) -> Vec<String> {
+        let mut owned_globs = self.owned_globs.clone();
+        if config.app_code_dirs > 0 {
+            owned_globs = owned_globs
+                .into_iter()
+                .flat_map(|glob| {
+                    if glob == APP_GLOB {
+                        app_code::INFRA_APP_GLOBS
+                            .iter()
+                            .map(|glob| glob.to_string())
+                            .collect()
+                    } else {
+                        vec![glob]
+                    }
+                })
+                .collect();
+        }
+        if config.spec_support {
+            for glob in spec_support::INFRA_SPEC_GLOBS {
+                if !owned_globs.iter().any(|owned| owned == glob) {
+                    owned_globs.push(glob.to_string());
+                }
+            }
+        }
+        owned_globs
+    }
+}
@@ -1,9 +1,97 @@
-use std::{path::PathBuf, process::Command};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+};
 
+use annotations::AnnotationStyle;
 use convert_case::{Case, Casing};
+#[cfg(feature = "download")]
+use download::Downloads;
 use faker_rand::en_us::names::FirstName;
+#[cfg(not(feature = "download"))]
+use offline::Downloads;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use state::GenerationState;
+use teams::Teams;
 
-#[derive(Debug)]
+mod annotations;
+mod app_code;
+mod bench;
+mod cancellation;
+mod case_twins;
+mod code_ownership;
+mod codeowners_file;
+mod dotslash;
+#[cfg(feature = "download")]
+mod download;
+mod events;
+mod fuzz;
+mod generated_app;
+mod hooks;
+mod infra_team;
+mod long_paths;
+mod malformed;
+mod manifest;
+mod members;
+mod metrics;
+mod mutate;
+mod namespaces;
+mod nested_codeowners;
+mod noise;
+mod odd_files;
+#[cfg(not(feature = "download"))]
+mod offline;
+mod ownership_table;
+mod pack_docs;
+mod package_root;
+mod package_yml;
+mod preset;
+mod report;
+mod skeleton;
+mod spec_support;
+mod state;
+mod syntax_errors;
+mod teams;
+mod timestamps;
+mod tools;
+
+pub use annotations::{EdgeCase, EdgeCaseExpectation};
+pub use bench::{compare, run_bench, Baselines, BenchConfig, BenchResult, Comparison};
+pub use cancellation::{CancellationToken, Cancelled};
+pub use code_ownership::CodeOwnershipShape;
+pub use codeowners_file::{CodeownersFile, CodeownersFormat, OwnershipOutput};
+pub use dotslash::DotslashArtifact;
+pub use events::{GenerationEvent, InjectedFaults, Phase};
+pub use fuzz::{run_case, run_fuzz, Finding, FuzzCase, FuzzConfig};
+pub use generated_app::GeneratedApp;
+pub use infra_team::{GithubTeam, InfraTeam};
+pub use malformed::MalformedKind;
+pub use manifest::{
+    AnnotationEdgeCase, AppCodeDir, CaseTwin, CoOwner, MalformedFile, Manifest, NestedCodeowner,
+    NoiseDir, OddFile, PackManifest, SupportFile, SyntaxErrorFile, ToolManifest, Violation,
+};
+pub use members::TeamSize;
+pub use metrics::{Metrics, MetricsSink};
+pub use mutate::{touch, watch, Change, Touched, WatchConfig};
+pub use namespaces::PackNamespaces;
+pub use odd_files::OddFileKind;
+pub use ownership_table::TableFormat;
+pub use package_root::PackageRoot;
+pub use package_yml::{PackageMetadata, PackageOwnerKey};
+pub use preset::{Preset, ViolationKind};
+pub use skeleton::RailsFlavor;
+pub use syntax_errors::SyntaxErrorKind;
+pub use teams::TeamLayout;
+pub use timestamps::FileTimes;
+pub use tools::{ToolOutcome, ToolRun};
+
+#[derive(Debug, Default, Clone)]
 pub struct Config {
     pub rails_path: String,
     pub base_dir: String,
@@ -11,16 +99,330 @@ pub struct Config {
     pub num_packages: usize,
     pub codeowners_dotslash_path: String,
     pub pks_dotslash_path: String,
+    /// Checked between phases and packs; cancelling it stops generation with [`Cancelled`].
+    pub cancellation: CancellationToken,
+    /// Where counters and timings go as apps are generated.
+    pub metrics: Metrics,
+    /// Seeds every random choice. A random seed is picked when unset.
+    pub seed: Option<u64>,
+    /// Continue from the checkpoint left in the app directory by an interrupted run instead
+    /// of starting over.
+    pub resume: bool,
+    /// What to do when the app directory already exists. Ignored when resuming.
+    pub on_exists: OnExists,
+    /// Run the downloaded tools' validations against the finished app, reporting each result
+    /// as a [`GenerationEvent::ToolFinished`].
+    pub run_tools: bool,
+    /// Shell commands run in order in the finished app, after any tool runs, for pushing it
+    /// to a fixtures repository, indexing it and the like. Generation fails if one does.
+    pub post_generation_hooks: Vec<String>,
+    /// Write the skeleton bundled with this crate instead of running `rails new`, so no Ruby
+    /// installation is needed. `rails_path` is ignored when set.
+    pub embedded_skeleton: bool,
+    /// Which Rails version's layout the embedded skeleton approximates.
+    pub rails_flavor: RailsFlavor,
+    /// When non-empty, one app is generated per entry instead of a single app. Each is named
+    /// `<app_name>_<suffix>` and tagged with its Rails source in its manifest.
+    pub matrix: Vec<RailsSource>,
+    /// How many matrix apps are built at once. With more than one, their events arrive
+    /// interleaved, each wrapped in a [`GenerationEvent::App`].
+    pub jobs: usize,
+    /// When set, `.ruby-version`, `.tool-versions` and `mise.toml` pinning this Ruby version
+    /// are written to the app root.
+    pub ruby_version: Option<String>,
+    /// When non-empty, `.dotslash/pks` is written as a dotslash descriptor covering these
+    /// platforms instead of the binary downloaded from `pks_dotslash_path`.
+    pub pks_dotslash_artifacts: Vec<DotslashArtifact>,
+    /// Same as `pks_dotslash_artifacts`, for `.dotslash/codeowners-rs`.
+    pub codeowners_dotslash_artifacts: Vec<DotslashArtifact>,
+    /// Resolve pks through its GitHub releases instead of using `pks_dotslash_path`: `latest`,
+    /// an exact tag, or a pattern such as `v0.2.*`. The resolved tag is recorded in the manifest.
+    pub pks_version: Option<String>,
+    /// Same as `pks_version`, for codeowners-rs.
+    pub codeowners_version: Option<String>,
+    /// PEM file of extra root certificates to trust for downloads, e.g. a corporate proxy's CA.
+    pub ca_bundle: Option<String>,
+    /// Skip TLS certificate verification for downloads. Only for networks where nothing else
+    /// works.
+    pub insecure_tls: bool,
+    /// Percentage (0-100) of packs whose `app/services` directories are split between the
+    /// pack's team and a second `<pack>-partner-team`. Packs owned through `package.yml` are
+    /// never split.
+    pub co_owned_percent: u8,
+    /// Percentage (0-100) of packs generated with no owner and no team, for exercising
+    /// unowned-file reporting.
+    pub ownerless_percent: u8,
+    /// Percentage (0-100) of pack teams generated under a new name. The old-to-new mapping
+    /// goes to `team-aliases.yml` and the pre-rename ground truth to
+    /// `generator-manifest.pre-rename.json`, next to the manifest.
+    pub team_rename_percent: u8,
+    /// Distribution of `github.members` counts for generated teams.
+    pub team_size: TeamSize,
+    /// Where team YAML is written; `team_file_glob` in code_ownership.yml follows it.
+    pub team_layout: TeamLayout,
+    pub code_ownership_shape: CodeOwnershipShape,
+    /// Whether ownership is configured through `config/code_ownership.yml`, hand-written
+    /// CODEOWNERS files matching the manifest, or both.
+    pub ownership_output: OwnershipOutput,
+    /// The CODEOWNERS files written when [`Config::ownership_output`] asks for them. GitHub's
+    /// `.github/CODEOWNERS` when empty.
+    pub codeowners_files: Vec<CodeownersFile>,
+    /// Globs appended to `owned_globs` in `config/code_ownership.yml`, e.g. to reproduce a
+    /// real app's configuration.
+    pub extra_owned_globs: Vec<String>,
+    /// Globs appended to `unowned_globs` in `config/code_ownership.yml`.
+    pub extra_unowned_globs: Vec<String>,
+    /// Vary what surrounds `# @team` annotations: magic comments, shebangs and whitespace.
+    pub varied_annotations: bool,
+    /// Add files that trip up annotation parsers to every annotation-owned pack, listed in
+    /// the manifest with what tools are expected to make of them.
+    pub pathological_annotations: bool,
+    /// Percentage (0-100) of packs that get a multi-megabyte Ruby file, a binary blob and an
+    /// empty file among their code.
+    pub odd_files_percent: u8,
+    /// Percentage (0-100) of packs that get a file nested in 255-character directories, with
+    /// an absolute path just under `PATH_MAX`.
+    pub long_paths_percent: u8,
+    /// Percentage (0-100) of packs that get files and a twin pack differing from theirs only
+    /// by case. The twins are skipped on case-insensitive filesystems.
+    pub case_twins_percent: u8,
+    /// Percentage (0-100) of packs that break one configuration file: their team's YAML,
+    /// their `package.yml`, or an entry they add to `config/code_ownership.yml`.
+    pub malformed_percent: u8,
+    /// Percentage (0-100) of directory-owned packs with `.codeowner` files nested below the
+    /// pack root: one handing a subtree to a `<pack>-nested-team`, and one inside that handing
+    /// its own subtree back.
+    pub nested_codeowners_percent: u8,
+    /// Percentage (0-100) of packs that get Ruby files which don't parse: an unbalanced `end`,
+    /// a missing one and stray bytes. Tools analysing constants should report them and finish.
+    pub syntax_error_percent: u8,
+    /// Sections written under `metadata:` in the `package.yml` of packs owned through it.
+    pub package_metadata: Vec<PackageMetadata>,
+    /// Whether those packs declare their owner at the top level of `package.yml` or under
+    /// `metadata:`.
+    pub package_owner_key: PackageOwnerKey,
+    /// Directories packs are spread across, each pack going under one picked at random. Empty
+    /// means `packs/` alone; otherwise `packwerk.yml` lists them as `package_paths` and
+    /// `config/code_ownership.yml` owns their Ruby files.
+    pub package_roots: Vec<PackageRoot>,
+    /// Groups packs into namespaces, each owned by one team the same way under one package
+    /// root. Namespaced packs are never co-owned, renamed, ownerless or malformed, since that
+    /// would split the namespace's ownership.
+    pub pack_namespaces: PackNamespaces,
+    /// Give every pack a `README.md` and a `docs/` folder. Markdown isn't in the default
+    /// `owned_globs`, so whether tools count these as owned depends on the configured globs.
+    pub pack_docs: bool,
+    /// Domain directories of plain Rails code to write under `app/models`, `app/controllers`
+    /// and `app/services`, outside any pack, each owned by a generated team. The infra team
+    /// then only owns the skeleton's files under `app`.
+    pub app_code_dirs: usize,
+    /// Write `spec/spec_helper.rb`, `spec/rails_helper.rb` and `spec/support`, owned by the
+    /// infra team through globs, with support directories for a few pack teams owned through
+    /// annotations.
+    pub spec_support: bool,
+    /// Files spread over `node_modules`, `vendor/bundle`, `tmp/cache` and `log`, where tools
+    /// shouldn't look. Tens of thousands of them show how well tools filter their traversal.
+    pub noise_files: usize,
+    /// The team owning the app outside its packs. Annotations that deliberately point at
+    /// another existing team use it too.
+    pub infra_team: InfraTeam,
+    /// When set, a `path,team,strategy` table of every file in the app and its intended owner
+    /// is written next to the manifest, for joining against tool output.
+    pub ownership_table: Option<TableFormat>,
+    /// When set, the mtime every generated path is left with, so archived apps are identical
+    /// byte for byte and mtime-based tool caches start from a known state. Applied last, after
+    /// tool runs and hooks, so nothing they write or touch is left with the current time.
+    pub file_times: Option<FileTimes>,
+    /// Generate a self-contained test fixture: the embedded skeleton instead of `rails new`,
+    /// no tool downloads and nothing that depends on the app's absolute location. Requires
+    /// [`Config::seed`].
+    pub fixture_export: bool,
+    /// Applied over the other options when generation starts.
+    pub preset: Option<Preset>,
+}
+
+/// One entry of [`Config::matrix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RailsSource {
+    /// Run `rails new` with this binary.
+    Binary(String),
+    /// Write the embedded skeleton for this flavor.
+    Flavor(RailsFlavor),
+}
+
+impl FromStr for RailsSource {
+    type Err = anyhow::Error;
+
+    /// Anything that isn't a known flavor is treated as a path to a `rails` binary.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<RailsFlavor>() {
+            Ok(flavor) => Self::Flavor(flavor),
+            Err(_) => Self::Binary(s.to_string()),
+        })
+    }
+}
+
+/// Policy for an app directory left behind by an earlier run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnExists {
+    /// Refuse to touch the existing directory.
+    #[default]
+    Fail,
+    /// Delete the directory and generate a fresh app in its place.
+    Overwrite,
+    /// Keep the existing app and add newly generated packs to it.
+    Merge,
+}
+
+impl FromStr for OnExists {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fail" => Ok(Self::Fail),
+            "overwrite" => Ok(Self::Overwrite),
+            "merge" => Ok(Self::Merge),
+            _ => anyhow::bail!(
+                "unknown on-exists policy '{}' (expected fail, overwrite or merge)",
+                s
+            ),
+        }
+    }
 }
 
 impl Config {
     pub fn app_dir(&self) -> PathBuf {
         PathBuf::from(&self.base_dir).join(&self.app_name)
     }
+
+    /// Identifies where the app's Rails layout came from: the flavor version for embedded
+    /// skeletons, otherwise the `rails` binary path.
+    pub fn rails_tag(&self) -> String {
+        if self.uses_embedded_skeleton() {
+            self.rails_flavor.version().to_string()
+        } else {
+            self.rails_path.clone()
+        }
+    }
+
+    /// Fixture exports never run `rails new`, whatever [`Config::embedded_skeleton`] says.
+    fn uses_embedded_skeleton(&self) -> bool {
+        self.embedded_skeleton || self.fixture_export
+    }
+
+    /// Rejects options that need a cargo feature this build was compiled without, before
+    /// anything is written.
+    fn validate_features(&self) -> anyhow::Result<()> {
+        if !cfg!(feature = "download") {
+            anyhow::ensure!(
+                self.fixture_export,
+                "built without the `download` feature, so the tools can't be downloaded; only \
+                 fixture exports can be generated"
+            );
+        }
+        if !cfg!(feature = "rails") {
+            let runs_rails = if self.matrix.is_empty() {
+                !self.uses_embedded_skeleton()
+            } else {
+                self.matrix
+                    .iter()
+                    .any(|source| matches!(source, RailsSource::Binary(_)))
+            };
+            anyhow::ensure!(
+                !runs_rails,
+                "built without the `rails` feature, so `rails new` can't be run; use the \
+                 embedded skeleton"
+            );
+        }
+        anyhow::ensure!(
+            cfg!(feature = "parquet") || self.ownership_table != Some(TableFormat::Parquet),
+            "built without the `parquet` feature, so the ownership table can only be CSV"
+        );
+        Ok(())
+    }
+
+    /// Rejects options that would make a [`Config::fixture_export`] depend on the machine it
+    /// was generated on.
+    fn validate_fixture_export(&self) -> anyhow::Result<()> {
+        if !self.fixture_export {
+            return Ok(());
+        }
+        anyhow::ensure!(
+            self.seed.is_some(),
+            "a fixture export needs a fixed seed so it can be regenerated identically"
+        );
+        anyhow::ensure!(
+            self.long_paths_percent == 0,
+            "long paths depend on where the app is written, so they can't go in a fixture"
+        );
+        anyhow::ensure!(
+            !self.run_tools,
+            "fixture exports don't download the tools, so they can't be run"
+        );
+        anyhow::ensure!(
+            !self
+                .matrix
+                .iter()
+                .any(|source| matches!(source, RailsSource::Binary(_))),
+            "fixture exports use the embedded skeletons; the matrix can only list flavors"
+        );
+        Ok(())
+    }
+
+    /// The single-app configs a matrix run expands to. They share one seed so every app gets
+    /// the same packs and only the Rails layout differs.
+    fn matrix_apps(&self) -> Vec<Config> {
+        let mut apps = self
+            .matrix
+            .iter()
+            .enumerate()
+            .map(|(index, source)| {
+                let mut app = Config {
+                    matrix: Vec::new(),
+                    ..self.clone()
+                };
+                match source {
+                    RailsSource::Binary(rails_path) => {
+                        app.app_name = format!("{}_rails_{}", self.app_name, index);
+                        app.rails_path = rails_path.clone();
+                        app.embedded_skeleton = false;
+                    }
+                    RailsSource::Flavor(flavor) => {
+                        app.app_name = format!(
+                            "{}_rails_{}",
+                            self.app_name,
+                            flavor.version().replace('.', "_")
+                        );
+                        app.embedded_skeleton = true;
+                        app.rails_flavor = *flavor;
+                    }
+                }
+                app
+            })
+            .collect::<Vec<_>>();
+
+        let recorded_seed = || {
+            apps.iter().find_map(|app| {
+                let app_dir = app.app_dir();
+                GenerationState::load(&app_dir)
+                    .map(|state| state.seed)
+                    .or_else(|_| Manifest::load(&app_dir).map(|manifest| manifest.seed))
+                    .ok()
+            })
+        };
+        let seed = self
+            .seed
+            .or_else(|| if self.resume { recorded_seed() } else { None })
+            .unwrap_or_else(rand::random);
+        for app in &mut apps {
+            app.seed = Some(seed);
+        }
+        apps
+    }
 }
 
-fn random_name() -> String {
-    rand::random::<FirstName>()
+fn random_name(rng: &mut impl Rng) -> String {
+    rng.gen::<FirstName>()
         .to_string()
         .to_case(Case::Snake)
         .chars()
@@ -28,100 +430,1054 @@ fn random_name() -> String {
         .collect::<String>()
 }
 
-fn packages(num: &usize) -> Vec<String> {
-    (0..*num).map(|_| random_name()).collect()
+/// Each pack draws from its own RNG derived from the run seed, so any pack can be regenerated
+/// identically without replaying the packs before it.
+fn pack_rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(seed ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Everything decided about a pack before any of it is written.
+struct PackPlan {
+    name: String,
+    ownership: PackOwnership,
+    co_owned: bool,
+    renamed: bool,
+    /// GitHub handles for the pack's team and, when co-owned, its partner team.
+    members: Vec<String>,
+    co_owner_members: Vec<String>,
+    /// The `app/services` directory that gets this pack's [`OddFileKind`] files, if any.
+    odd_files_dir: Option<&'static str>,
+    long_path: bool,
+    case_twins: bool,
+    malformed: Option<MalformedKind>,
+    nested_codeowners: bool,
+    /// The `app/services` directory that gets this pack's [`SyntaxErrorKind`] files, if any.
+    syntax_errors_dir: Option<&'static str>,
+    product_group: Option<&'static str>,
+    owner_in_metadata: bool,
+    root: PackageRoot,
+    namespace: Option<&'static str>,
+}
+
+/// Salt for the RNG that draws team members. Keeping members off the pack's own RNG means
+/// changing [`Config::team_size`] leaves every pack's name and contents alone.
+const MEMBERS_SEED_SALT: u64 = 0x6D65_6D62_6572_7321;
+
+/// Salt for the RNG behind `package.yml` metadata, see [`MEMBERS_SEED_SALT`].
+const METADATA_SEED_SALT: u64 = 0x6D65_7461_6461_7461;
+
+/// Salt for the RNG placing `package.yml` owners with [`PackageOwnerKey::Random`].
+const OWNER_KEY_SEED_SALT: u64 = 0x6F77_6E65_726B_6579;
+
+/// Salt for the RNG picking each pack's [`PackageRoot`].
+const ROOT_SEED_SALT: u64 = 0x726F_6F74_7061_7468;
+
+/// Salt for the RNG picking each pack's namespace.
+const NAMESPACE_SEED_SALT: u64 = 0x6E61_6D65_7370_6163;
+
+/// Salt for the RNG behind what a namespace's packs share: how they're owned, the team's
+/// members and the package root. It is seeded with the namespace's index rather than a pack's.
+const NAMESPACE_TEAM_SEED_SALT: u64 = 0x6E73_7465_616D_7321;
+
+/// Plans the pack at `index`, returning the RNG positioned to generate its contents. Optional
+/// features only draw from the RNG when they are enabled, so a seed keeps producing the same
+/// app as long as the options it was used with stay the same.
+fn plan_pack(config: &Config, seed: u64, index: usize) -> (StdRng, PackPlan) {
+    let mut rng = pack_rng(seed, index);
+    let coverage_pack = (config.preset == Some(Preset::Coverage))
+        .then(|| preset::COVERAGE_PACKS[index % preset::COVERAGE_PACKS.len()]);
+    let namespace = config
+        .pack_namespaces
+        .pick(&mut pack_rng(seed ^ NAMESPACE_SEED_SALT, index));
+    let mut namespace_rng =
+        namespace.map(|(namespace, _)| pack_rng(seed ^ NAMESPACE_TEAM_SEED_SALT, namespace));
+    let namespace = namespace.map(|(_, namespace)| namespace);
+    let name = match coverage_pack {
+        Some((name, _)) => name.to_string(),
+        None => random_name(&mut rng),
+    };
+    let name = match namespace {
+        Some(namespace) => config.pack_namespaces.pack_name(namespace, &name),
+        None => name,
+    };
+    let ownerless = namespace.is_none()
+        && config.ownerless_percent > 0
+        && rng.gen_range(0..100) < config.ownerless_percent;
+    let ownership = if let Some((_, ownership)) = coverage_pack {
+        ownership
+    } else if let Some(namespace_rng) = &mut namespace_rng {
+        PackOwnership::random(namespace_rng)
+    } else if ownerless {
+        PackOwnership::Unowned
+    } else {
+        PackOwnership::random(&mut rng)
+    };
+    let co_owned = namespace.is_none()
+        && config.co_owned_percent > 0
+        && ownership.supports_co_ownership()
+        && rng.gen_range(0..100) < config.co_owned_percent;
+    let renamed = namespace.is_none()
+        && config.team_rename_percent > 0
+        && ownership != PackOwnership::Unowned
+        && rng.gen_range(0..100) < config.team_rename_percent;
+    let odd_files_dir = (config.odd_files_percent > 0
+        && rng.gen_range(0..100) < config.odd_files_percent)
+        .then(|| CODE_DIRECTORIES[rng.gen_range(0..CODE_DIRECTORIES.len())]);
+    let long_path =
+        config.long_paths_percent > 0 && rng.gen_range(0..100) < config.long_paths_percent;
+    let case_twins =
+        config.case_twins_percent > 0 && rng.gen_range(0..100) < config.case_twins_percent;
+    let malformed = (namespace.is_none()
+        && config.malformed_percent > 0
+        && rng.gen_range(0..100) < config.malformed_percent)
+        .then(|| MalformedKind::random(ownership, &mut rng));
+    let nested_codeowners = config.nested_codeowners_percent > 0
+        && ownership == PackOwnership::Directory
+        && rng.gen_range(0..100) < config.nested_codeowners_percent;
+    let syntax_errors_dir = (config.syntax_error_percent > 0
+        && rng.gen_range(0..100) < config.syntax_error_percent)
+        .then(|| CODE_DIRECTORIES[rng.gen_range(0..CODE_DIRECTORIES.len())]);
+    let mut members_rng = pack_rng(seed ^ MEMBERS_SEED_SALT, index);
+    let members = config
+        .team_size
+        .members(namespace_rng.as_mut().unwrap_or(&mut members_rng));
+    let co_owner_members = if co_owned {
+        config.team_size.members(&mut members_rng)
+    } else {
+        Vec::new()
+    };
+    let product_group = config
+        .package_metadata
+        .contains(&PackageMetadata::ProductGroup)
+        .then(|| package_yml::product_group(&mut pack_rng(seed ^ METADATA_SEED_SALT, index)));
+    let owner_in_metadata = ownership == PackOwnership::PackConfig
+        && config
+            .package_owner_key
+            .in_metadata(&mut pack_rng(seed ^ OWNER_KEY_SEED_SALT, index));
+    let root = PackageRoot::pick(
+        &config.package_roots,
+        namespace_rng
+            .as_mut()
+            .unwrap_or(&mut pack_rng(seed ^ ROOT_SEED_SALT, index)),
+    );
+    (
+        rng,
+        PackPlan {
+            name,
+            ownership,
+            co_owned,
+            renamed,
+            members,
+            co_owner_members,
+            odd_files_dir,
+            long_path,
+            case_twins,
+            malformed,
+            nested_codeowners,
+            syntax_errors_dir,
+            product_group,
+            owner_in_metadata,
+            root,
+            namespace,
+        },
+    )
 }
 
-pub fn build_app(config: Config) -> anyhow::Result<()> {
-    setup_rails_app(&config)?;
-    setup_dotslash_tools(&config)?;
-    setup_infra_team(&config)?;
+/// Generates the app `config` describes, or one per [`Config::matrix`] entry in matrix order.
+pub fn build_app(config: Config) -> anyhow::Result<Vec<GeneratedApp>> {
+    build_app_with_observer(config, |_| {})
+}
 
-    packages(&config.num_packages)
+/// Same as [`build_app`], but reports progress to `on_event` as generation moves through
+/// its phases and completes each pack.
+pub fn build_app_with_observer(
+    config: Config,
+    on_event: impl FnMut(&GenerationEvent),
+) -> anyhow::Result<Vec<GeneratedApp>> {
+    build_app_sharing(config, None, on_event)
+}
+
+/// Same as [`build_app_with_observer`], taking the tools from `shared` rather than downloading
+/// them afresh, when given.
+pub(crate) fn build_app_sharing(
+    config: Config,
+    shared: Option<&Downloads>,
+    mut on_event: impl FnMut(&GenerationEvent),
+) -> anyhow::Result<Vec<GeneratedApp>> {
+    let mut config = config;
+    if let Some(preset) = config.preset {
+        preset.apply(&mut config);
+    }
+    config.validate_fixture_export()?;
+    config.validate_features()?;
+    anyhow::ensure!(
+        config.ownership_output.writes_code_ownership() || config.malformed_percent == 0,
+        "malformed files can break config/code_ownership.yml, which the codeowners ownership output doesn't write"
+    );
+    let owned;
+    let downloads = match shared {
+        _ if config.fixture_export => None,
+        Some(shared) => Some(shared),
+        None => {
+            owned = Downloads::new(&config)?;
+            Some(&owned)
+        }
+    };
+    if config.matrix.is_empty() {
+        return Ok(vec![build_single_app(&config, downloads, &mut on_event)?]);
+    }
+    let mut apps = Vec::new();
+    let mut finished = Vec::new();
+    for (index, mut app_config) in config.matrix_apps().into_iter().enumerate() {
+        let app_dir = app_config.app_dir();
+        if app_config.resume && !GenerationState::path(&app_dir).exists() {
+            if Manifest::path(&app_dir).exists() {
+                // Finished before the run was interrupted.
+                finished.push((index, GeneratedApp::load(&app_config)?));
+                continue;
+            }
+            app_config.resume = false;
+        }
+        apps.push((index, app_config));
+    }
+    let built = if config.jobs > 1 && apps.len() > 1 {
+        build_concurrently(&apps, config.jobs, downloads, &mut on_event)?
+    } else {
+        let mut built = Vec::new();
+        for (index, app_config) in &apps {
+            on_event(&GenerationEvent::AppStarted {
+                app_name: app_config.app_name.clone(),
+                rails: app_config.rails_tag(),
+            });
+            built.push((
+                *index,
+                build_single_app(app_config, downloads, &mut on_event)?,
+            ));
+        }
+        built
+    };
+    finished.extend(built);
+    finished.sort_by_key(|(index, _)| *index);
+    Ok(finished.into_iter().map(|(_, app)| app).collect())
+}
+
+/// Builds `apps` on up to `jobs` threads sharing `downloads`. Their events are passed to
+/// `on_event` on the calling thread as they arrive. After an app fails no new apps are
+/// started, and the first failure in matrix order is returned once the others finish.
+fn build_concurrently(
+    apps: &[(usize, Config)],
+    jobs: usize,
+    downloads: Option<&Downloads>,
+    on_event: &mut impl FnMut(&GenerationEvent),
+) -> anyhow::Result<Vec<(usize, GeneratedApp)>> {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..jobs.min(apps.len()))
+            .map(|_| {
+                let (next, failed, sender) = (&next, &failed, sender.clone());
+                scope.spawn(move || {
+                    let mut results = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((matrix_index, app_config)) = apps.get(index) else {
+                            break;
+                        };
+                        let app_name = app_config.app_name.clone();
+                        // The observer only goes away once every worker has finished.
+                        let _ = sender.send(GenerationEvent::AppStarted {
+                            app_name: app_name.clone(),
+                            rails: app_config.rails_tag(),
+                        });
+                        let result = build_single_app(app_config, downloads, &mut |event| {
+                            let _ = sender.send(GenerationEvent::App {
+                                app_name: app_name.clone(),
+                                event: Box::new(event.clone()),
+                            });
+                        });
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        results.push((*matrix_index, result));
+                    }
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+        for event in receiver {
+            on_event(&event);
+        }
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("app builder panicked"))
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results
         .into_iter()
-        .map(|pack| {
-            let ownership = PackOwnership::random();
-            let pack_config = PackConfig::new(&config, &pack, ownership);
-            build_pack(&pack_config)
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|(index, result)| Ok((index, result?)))
+        .collect()
+}
 
+fn build_single_app(
+    config: &Config,
+    downloads: Option<&Downloads>,
+    on_event: &mut impl FnMut(&GenerationEvent),
+) -> anyhow::Result<GeneratedApp> {
+    let mut recorder = config.metrics.recorder(config);
+    let on_event = &mut |event: &GenerationEvent| {
+        if let Some(recorder) = &mut recorder {
+            recorder.observe(event);
+        }
+        on_event(event)
+    };
+    let total = config.num_packages;
+    let check_cancelled = |completed_packs| {
+        if config.cancellation.is_cancelled() {
+            Err(Cancelled {
+                completed_packs,
+                total_packs: total,
+            })
+        } else {
+            Ok(())
+        }
+    };
+
+    let app_dir = config.app_dir();
+    let mut state = if config.resume {
+        let state = GenerationState::load(&app_dir)?;
+        validate_resume(config, &state)?;
+        on_event(&GenerationEvent::Resumed {
+            completed_packs: state.completed_packs,
+            total,
+        });
+        state
+    } else {
+        let merging = prepare_app_dir(config)?;
+        let resolved = match downloads {
+            Some(downloads) => {
+                let (pks, codeowners) = downloads.resolve_tools(config)?;
+                Some((downloads, pks, codeowners))
+            }
+            None => None,
+        };
+        let seed = config.seed.unwrap_or_else(rand::random);
+        check_cancelled(0)?;
+        if !merging {
+            let shape = config.code_ownership_shape.resolve(seed);
+            run_phase(on_event, Phase::RailsApp, || setup_rails_app(config, shape))?;
+        }
+        check_cancelled(0)?;
+        let mut tools = Vec::new();
+        if let Some((downloads, pks, codeowners)) = resolved {
+            run_phase(on_event, Phase::DotslashTools, || {
+                setup_dotslash_tools(config, downloads, &pks.url, &codeowners.url)
+            })?;
+            tools = vec![pks, codeowners];
+        }
+        check_cancelled(0)?;
+        if !merging {
+            run_phase(on_event, Phase::InfraTeam, || setup_infra_team(config))?;
+        }
+
+        let state = GenerationState::new(seed, total, tools);
+        state.save(&app_dir)?;
+        state
+    };
+
+    // Only an app merged into has a manifest before its packs are built.
+    let mut manifest = if Manifest::path(&app_dir).exists() {
+        Manifest::load(&app_dir)?
+    } else {
+        Manifest {
+            app_name: config.app_name.clone(),
+            rails: config.rails_tag(),
+            seed: state.seed,
+            code_ownership_shape: config.code_ownership_shape.resolve(state.seed),
+            tools: Vec::new(),
+            packs: Vec::new(),
+            app_code: Vec::new(),
+            spec_support: Vec::new(),
+            noise: Vec::new(),
+        }
+    };
+    manifest.tools = state.tools.clone();
+    // The checkpoint is synced after each pack but the pack's own files aren't, so a resumed
+    // run rebuilds the last completed pack too in case a power loss left it half on disk.
+    let first_pack = if config.resume {
+        state.completed_packs.saturating_sub(1)
+    } else {
+        state.completed_packs
+    };
+    for index in 0..first_pack {
+        let (_, plan) = plan_pack(config, state.seed, index);
+        if !manifest.packs.iter().any(|built| built.name == plan.name) {
+            manifest
+                .packs
+                .push(PackConfig::new(config, &plan).manifest()?);
+        }
+    }
+
+    on_event(&GenerationEvent::PhaseStarted(Phase::Packs));
+    let mut teams = Teams::load(config.team_layout, &app_dir)?;
+    let mut files_written = 0;
+    for index in first_pack..total {
+        check_cancelled(index)?;
+        let (mut rng, plan) = plan_pack(config, state.seed, index);
+        let pack_config = PackConfig::new(config, &plan);
+        if config.resume && index <= state.completed_packs {
+            discard_partial_pack(&pack_config, &mut teams, state.seed, index)?;
+        }
+        let event = match build_pack(&pack_config, &mut teams, &mut rng)? {
+            PackResult::Built { files } => {
+                files_written += files;
+                manifest.packs.push(pack_config.manifest()?);
+                GenerationEvent::PackCompleted {
+                    name: plan.name,
+                    index,
+                    total,
+                    files_written: files,
+                }
+            }
+            PackResult::Skipped => GenerationEvent::PackSkipped {
+                name: plan.name,
+                index,
+                total,
+            },
+        };
+        state.completed_packs = index + 1;
+        state.save(&app_dir)?;
+        on_event(&event);
+    }
+    on_event(&GenerationEvent::PhaseFinished(Phase::Packs));
+
+    let teams = manifest
+        .packs
+        .iter()
+        .filter_map(|pack| pack.team.clone())
+        .collect::<Vec<_>>();
+    if config.app_code_dirs > 0 {
+        check_cancelled(total)?;
+        run_phase(on_event, Phase::AppCode, || {
+            let (dirs, files) = app_code::write(config, state.seed, &teams)?;
+            merge_by_path(&mut manifest.app_code, dirs, |dir| &dir.path);
+            files_written += files;
+            Ok(())
+        })?;
+    }
+    if config.spec_support {
+        check_cancelled(total)?;
+        run_phase(on_event, Phase::SpecSupport, || {
+            let (support_files, files) =
+                spec_support::write(config, state.seed, &config.infra_team.name, &teams)?;
+            merge_by_path(&mut manifest.spec_support, support_files, |file| &file.path);
+            files_written += files;
+            Ok(())
+        })?;
+    }
+    if config.noise_files > 0 {
+        check_cancelled(total)?;
+        run_phase(on_event, Phase::Noise, || {
+            merge_by_path(
+                &mut manifest.noise,
+                noise::write(config, state.seed)?,
+                |dir| &dir.path,
+            );
+            files_written += config.noise_files;
+            Ok(())
+        })?;
+    }
+
+    if config.ownership_output.writes_codeowners() {
+        check_cancelled(total)?;
+        run_phase(on_event, Phase::Codeowners, || {
+            files_written += codeowners_file::write(config, &manifest)?;
+            Ok(())
+        })?;
+    }
+
+    manifest.write(&app_dir)?;
+    if config.team_rename_percent > 0 {
+        manifest.write_rename_states(&app_dir)?;
+    }
+    report::write(&app_dir, config, &manifest)?;
+    GenerationState::remove(&app_dir)?;
+    let app = GeneratedApp::new(config, manifest);
+    if let Some(format) = config.ownership_table {
+        ownership_table::write(&app_dir, app.files()?, format)?;
+    }
+    if config.run_tools {
+        on_event(&GenerationEvent::PhaseStarted(Phase::ToolRuns));
+        let generate_codeowners = !config.ownership_output.writes_codeowners();
+        for run in tools::run_tools(&app_dir, generate_codeowners) {
+            on_event(&GenerationEvent::ToolFinished(run));
+        }
+        on_event(&GenerationEvent::PhaseFinished(Phase::ToolRuns));
+    }
+    if !config.post_generation_hooks.is_empty() {
+        run_phase(on_event, Phase::Hooks, || {
+            hooks::run(config, &app_dir, state.seed)
+        })?;
+    }
+    if let Some(file_times) = config.file_times {
+        file_times.apply(&app_dir, state.seed)?;
+    }
+    on_event(&GenerationEvent::Finished {
+        files_written,
+        faults: InjectedFaults::new(&app.manifest),
+    });
+    Ok(app)
+}
+
+/// Adds `new` to `entries`, replacing those at the same path, so merging into an app keeps
+/// what it already had.
+fn merge_by_path<T>(entries: &mut Vec<T>, new: Vec<T>, path: impl Fn(&T) -> &Path) {
+    entries.retain(|entry| !new.iter().any(|added| path(added) == path(entry)));
+    entries.extend(new);
+}
+
+/// Applies [`Config::on_exists`] to the app directory. Returns true when packs should be
+/// merged into an app that is already there.
+fn prepare_app_dir(config: &Config) -> anyhow::Result<bool> {
+    let app_dir = config.app_dir();
+    if !app_dir.exists() {
+        return Ok(false);
+    }
+    match config.on_exists {
+        OnExists::Fail => anyhow::bail!(
+            "{} already exists; overwrite it or merge into it explicitly",
+            app_dir.display()
+        ),
+        OnExists::Overwrite => {
+            std::fs::remove_dir_all(&app_dir)?;
+            Ok(false)
+        }
+        OnExists::Merge => Ok(true),
+    }
+}
+
+fn validate_resume(config: &Config, state: &GenerationState) -> anyhow::Result<()> {
+    if state.num_packages != config.num_packages {
+        anyhow::bail!(
+            "checkpoint was created for {} packages, but {} were requested",
+            state.num_packages,
+            config.num_packages
+        );
+    }
+    if let Some(seed) = config.seed {
+        if seed != state.seed {
+            anyhow::bail!(
+                "checkpoint was created with seed {}, but seed {} was requested",
+                state.seed,
+                seed
+            );
+        }
+    }
     Ok(())
 }
 
-#[derive(Debug, PartialEq)]
-enum PackOwnership {
+/// The pack that was in progress when a previous run stopped, or the last one it completed, may
+/// be partially written, which would otherwise make it look like a team collision and get skipped.
+fn discard_partial_pack(
+    pack_config: &PackConfig,
+    teams: &mut Teams,
+    seed: u64,
+    index: usize,
+) -> anyhow::Result<()> {
+    let owned_by_earlier_pack = (0..index)
+        .any(|earlier| plan_pack(pack_config.config, seed, earlier).1.name == pack_config.name);
+    if owned_by_earlier_pack {
+        return Ok(());
+    }
+    teams.remove(&pack_config.team_name())?;
+    if let Some(co_owner) = pack_config.co_owner_team_name() {
+        teams.remove(&co_owner)?;
+    }
+    if pack_config.nested_codeowners {
+        teams.remove(&nested_codeowners::team_name(pack_config.name))?;
+    }
+    if pack_config.pack_path().exists() {
+        std::fs::remove_dir_all(pack_config.pack_path())?;
+    }
+    let twin_pack = pack_config
+        .config
+        .app_dir()
+        .join(pack_config.root.dir())
+        .join(case_twins::capitalized(pack_config.name));
+    if pack_config.case_twins && case_twins::exists_exactly(&twin_pack) {
+        std::fs::remove_dir_all(twin_pack)?;
+    }
+    Ok(())
+}
+
+fn run_phase(
+    on_event: &mut impl FnMut(&GenerationEvent),
+    phase: Phase,
+    f: impl FnOnce() -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    on_event(&GenerationEvent::PhaseStarted(phase));
+    f()?;
+    on_event(&GenerationEvent::PhaseFinished(phase));
+    Ok(())
+}
+
+/// How a pack's team is declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackOwnership {
+    /// A `.codeowner` file in the pack root.
     Directory,
+    /// A `# @team` comment at the top of every file.
     FileAnnotation,
+    /// An `owned_globs` entry in the team's YAML.
     TeamConfig,
+    /// An `owner:` key in the pack's `package.yml`.
     PackConfig,
+    /// No ownership signal at all, and no team is generated for the pack.
+    Unowned,
 }
 
 impl PackOwnership {
-    fn random() -> Self {
-        match rand::random::<u8>() % 4 {
+    /// The name it serializes as in the manifest.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Directory => "directory",
+            Self::FileAnnotation => "file_annotation",
+            Self::TeamConfig => "team_config",
+            Self::PackConfig => "pack_config",
+            Self::Unowned => "unowned",
+        }
+    }
+
+    fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen::<u8>() % 4 {
             0 => Self::Directory,
             1 => Self::FileAnnotation,
             2 => Self::TeamConfig,
             _ => Self::PackConfig,
         }
     }
+
+    /// `package.yml` ownership always covers the whole pack, so it can't be split between
+    /// teams without the two declarations overlapping.
+    fn supports_co_ownership(self) -> bool {
+        !matches!(self, Self::PackConfig | Self::Unowned)
+    }
 }
 
 struct PackConfig<'a> {
     config: &'a Config,
     name: &'a str,
     ownership: PackOwnership,
+    co_owned: bool,
+    renamed: bool,
+    members: &'a [String],
+    co_owner_members: &'a [String],
+    odd_files_dir: Option<&'static str>,
+    long_path: bool,
+    case_twins: bool,
+    malformed: Option<MalformedKind>,
+    nested_codeowners: bool,
+    syntax_errors_dir: Option<&'static str>,
+    product_group: Option<&'static str>,
+    owner_in_metadata: bool,
+    root: PackageRoot,
+    namespace: Option<&'static str>,
 }
 
 impl<'a> PackConfig<'a> {
-    fn new(config: &'a Config, name: &'a str, ownership: PackOwnership) -> Self {
+    fn new(config: &'a Config, plan: &'a PackPlan) -> Self {
         Self {
             config,
-            name,
-            ownership,
+            name: &plan.name,
+            ownership: plan.ownership,
+            co_owned: plan.co_owned,
+            renamed: plan.renamed,
+            members: &plan.members,
+            co_owner_members: &plan.co_owner_members,
+            odd_files_dir: plan.odd_files_dir,
+            long_path: plan.long_path,
+            case_twins: plan.case_twins,
+            malformed: plan.malformed,
+            nested_codeowners: plan.nested_codeowners,
+            syntax_errors_dir: plan.syntax_errors_dir,
+            root: plan.root,
+            namespace: plan.namespace,
+            product_group: plan.product_group,
+            owner_in_metadata: plan.owner_in_metadata,
         }
     }
     fn team_name(&self) -> String {
-        format!("{}-team", self.name)
+        if let Some(namespace) = self.namespace {
+            namespaces::team_name(namespace)
+        } else if self.renamed {
+            format!("{}-renamed-team", self.name)
+        } else {
+            format!("{}-team", self.name)
+        }
+    }
+    /// The name a renamed team had before [`Config::team_rename_percent`] renamed it.
+    fn previous_team_name(&self) -> Option<String> {
+        self.renamed.then(|| format!("{}-team", self.name))
+    }
+    /// The second team of a co-owned pack, which owns [`Self::co_owned_directories`] using
+    /// the same mechanism the pack's own team uses for the rest.
+    fn co_owner_team_name(&self) -> Option<String> {
+        self.co_owned.then(|| format!("{}-partner-team", self.name))
+    }
+    fn co_owned_directories(&self) -> &'static [&'static str] {
+        if self.co_owned {
+            &CODE_DIRECTORIES[CODE_DIRECTORIES.len() / 2..]
+        } else {
+            &[]
+        }
+    }
+    /// Directories with their own `.codeowner`, relative to the app root, and the team each
+    /// names.
+    fn nested_directories(&self) -> Vec<(PathBuf, String)> {
+        if !self.nested_codeowners {
+            return Vec::new();
+        }
+        nested_codeowners::directories(&self.relative_pack_path(), self.name, &self.team_name())
+    }
+    fn team_members(&self, team_name: &str) -> &'a [String] {
+        if self.co_owner_team_name().as_deref() == Some(team_name) {
+            self.co_owner_members
+        } else {
+            self.members
+        }
+    }
+    /// Files from [`Config::pathological_annotations`], only written to annotation-owned packs.
+    fn edge_cases(&self) -> &'static [EdgeCase] {
+        if self.config.pathological_annotations && self.ownership == PackOwnership::FileAnnotation {
+            &EdgeCase::ALL
+        } else {
+            &[]
+        }
+    }
+    fn edge_case_dir(&self) -> PathBuf {
+        self.pack_path().join("app/services/edge_cases")
+    }
+    /// The file from [`Config::long_paths_percent`], relative to the app root. It lives in
+    /// `app/services/a`, which is never co-owned, so it belongs to the pack's team.
+    fn long_path_file(&self) -> anyhow::Result<Option<PathBuf>> {
+        if !self.long_path {
+            return Ok(None);
+        }
+        let dir = self.relative_pack_path().join("app/services/a");
+        Ok(
+            long_paths::longest_file_path(&self.config.app_dir().join(&dir))?
+                .map(|path| dir.join(path)),
+        )
+    }
+    /// Pairs of paths, relative to the app root, that differ only by case: a file in
+    /// `app/services/a` and a capitalized copy beside it, and a file in this pack and its copy
+    /// in a capitalized twin of the pack. The twin pack has no ownership of its own, so only
+    /// an annotation in the file itself gives it an owner.
+    fn case_twin_pairs(&self) -> Vec<(PathBuf, PathBuf, Option<String>)> {
+        if !self.case_twins {
+            return Vec::new();
+        }
+        let team = (self.ownership != PackOwnership::Unowned).then(|| self.team_name());
+        let twin_pack_team = team
+            .clone()
+            .filter(|_| self.ownership == PackOwnership::FileAnnotation);
+        let services = self.relative_pack_path().join("app/services/a");
+        let twin_pack_services = PathBuf::from(self.root.dir())
+            .join(case_twins::capitalized(self.name))
+            .join("app/services/a");
+        let pack_file = format!("{}.rb", self.name);
+        vec![
+            (
+                services.join(format!("{}.rb", case_twins::TWIN_FILE_STEM)),
+                services.join(format!(
+                    "{}.rb",
+                    case_twins::capitalized(case_twins::TWIN_FILE_STEM)
+                )),
+                team,
+            ),
+            (
+                services.join(&pack_file),
+                twin_pack_services.join(&pack_file),
+                twin_pack_team,
+            ),
+        ]
+    }
+    /// The team a correct tool attributes an odd file to: nobody in unowned packs, and
+    /// nobody for unannotated files in annotation-owned packs.
+    fn odd_file_team(&self, dir: &str, kind: OddFileKind) -> Option<String> {
+        match self.ownership {
+            PackOwnership::Unowned => None,
+            PackOwnership::FileAnnotation if !kind.annotated() => None,
+            _ => Some(self.directory_team(dir)),
+        }
+    }
+    /// The team owning the files in `app/services/<dir>`.
+    fn directory_team(&self, dir: &str) -> String {
+        match self.co_owner_team_name() {
+            Some(co_owner) if self.co_owned_directories().contains(&dir) => co_owner,
+            _ => self.team_name(),
+        }
     }
     fn pack_path(&self) -> PathBuf {
-        self.config.app_dir().join("packs").join(self.name)
+        let mut path = self.config.app_dir().join(self.root.dir());
+        if let Some(namespace) = self
+            .namespace
+            .filter(|_| self.config.pack_namespaces == PackNamespaces::Nested)
+        {
+            path.push(namespace);
+        }
+        path.join(self.name)
     }
 
     fn relative_pack_path(&self) -> PathBuf {
         self.pack_path()
-            .strip_prefix(&self.config.app_dir())
+            .strip_prefix(self.config.app_dir())
             .unwrap()
             .to_path_buf()
     }
+
+    /// The `owned_globs` entry for `team_name` when the pack is owned through team YAML.
+    /// Co-owned packs split `app/services` between the two teams instead of claiming the
+    /// whole pack, and namespaced packs claim their whole namespace.
+    fn owned_glob(&self, team_name: &str) -> String {
+        if let Some(namespace) = self.namespace {
+            return self
+                .config
+                .pack_namespaces
+                .owned_glob(self.root.dir(), namespace);
+        }
+        if !self.co_owned {
+            return format!("{}/**", self.relative_pack_path().display());
+        }
+        let dirs = CODE_DIRECTORIES
+            .iter()
+            .filter(|dir| self.directory_team(dir) == team_name)
+            .copied()
+            .collect::<Vec<_>>();
+        format!(
+            "{}/app/services/{{{}}}/**",
+            self.relative_pack_path().display(),
+            dirs.join(",")
+        )
+    }
+
+    /// The ownership problem written into a [`Preset::Coverage`] pack, if its ownership gets
+    /// one.
+    fn violations(&self) -> Vec<Violation> {
+        if self.config.preset != Some(Preset::Coverage) {
+            return Vec::new();
+        }
+        let (file, kind) = match self.ownership {
+            PackOwnership::Directory => ("multiple_owners", ViolationKind::MultipleOwners),
+            PackOwnership::FileAnnotation => ("unknown_team", ViolationKind::UnknownTeam),
+            PackOwnership::Unowned => (self.name, ViolationKind::Unowned),
+            _ => return Vec::new(),
+        };
+        vec![Violation {
+            path: self
+                .relative_pack_path()
+                .join("app/services/a")
+                .join(format!("{}.rb", file)),
+            kind,
+        }]
+    }
+
+    fn manifest(&self) -> anyhow::Result<PackManifest> {
+        Ok(PackManifest {
+            name: self.name.to_string(),
+            path: self.relative_pack_path(),
+            namespace: self.namespace.map(str::to_string),
+            team: (self.ownership != PackOwnership::Unowned).then(|| self.team_name()),
+            previous_team: self.previous_team_name(),
+            ownership: self.ownership,
+            co_owner: self.co_owner_team_name().map(|team| CoOwner {
+                team,
+                directories: self
+                    .co_owned_directories()
+                    .iter()
+                    .map(|dir| self.relative_pack_path().join("app/services").join(dir))
+                    .collect(),
+            }),
+            nested_codeowners: self
+                .nested_directories()
+                .into_iter()
+                .map(|(directory, team)| NestedCodeowner { directory, team })
+                .collect(),
+            annotation_edge_cases: self
+                .edge_cases()
+                .iter()
+                .map(|case| AnnotationEdgeCase {
+                    path: self
+                        .relative_pack_path()
+                        .join("app/services/edge_cases")
+                        .join(case.file_name()),
+                    case: *case,
+                    expectation: case.expectation(),
+                })
+                .collect(),
+            odd_files: self
+                .odd_files_dir
+                .into_iter()
+                .flat_map(|dir| {
+                    OddFileKind::ALL.iter().map(move |kind| OddFile {
+                        path: self
+                            .relative_pack_path()
+                            .join("app/services")
+                            .join(dir)
+                            .join(kind.file_name()),
+                        kind: *kind,
+                        team: self.odd_file_team(dir, *kind),
+                    })
+                })
+                .collect(),
+            syntax_error_files: self
+                .syntax_errors_dir
+                .into_iter()
+                .flat_map(|dir| {
+                    SyntaxErrorKind::ALL
+                        .iter()
+                        .map(move |kind| SyntaxErrorFile {
+                            path: self
+                                .relative_pack_path()
+                                .join("app/services")
+                                .join(dir)
+                                .join(kind.file_name()),
+                            kind: *kind,
+                            team: (self.ownership != PackOwnership::Unowned)
+                                .then(|| self.directory_team(dir)),
+                        })
+                })
+                .collect(),
+            case_twins: self
+                .case_twin_pairs()
+                .into_iter()
+                .filter(|(_, twin, _)| {
+                    case_twins::exists_exactly(&self.config.app_dir().join(twin))
+                })
+                .map(|(original, path, team)| CaseTwin {
+                    path,
+                    twin_of: original,
+                    team,
+                })
+                .collect(),
+            long_path_file: self.long_path_file()?,
+            violations: self.violations(),
+            doc_files: if self.config.pack_docs {
+                pack_docs::doc_files(&self.relative_pack_path())
+            } else {
+                Vec::new()
+            },
+            malformed_file: self.malformed.map(|kind| MalformedFile {
+                path: kind.path(
+                    self.config.team_layout,
+                    &self.relative_pack_path(),
+                    &self.team_name(),
+                ),
+                kind,
+            }),
+        })
+    }
 }
 
-fn build_pack(pack_config: &PackConfig) -> anyhow::Result<()> {
-    let team_name = pack_config.team_name();
-    match setup_team_directory(pack_config, &team_name)? {
-        TeamSetupResult::Success => {}
-        TeamSetupResult::AlreadyExists => {
-            return Ok(());
+enum PackResult {
+    Built { files: usize },
+    Skipped,
+}
+
+fn build_pack(
+    pack_config: &PackConfig,
+    teams: &mut Teams,
+    rng: &mut impl Rng,
+) -> anyhow::Result<PackResult> {
+    if pack_config.ownership == PackOwnership::Unowned {
+        if pack_config.pack_path().exists() {
+            return Ok(PackResult::Skipped);
         }
+        setup_pack_directory(pack_config)?;
+        let code_files = generate_code_files(pack_config, rng)?;
+        let odd_files = write_odd_files(pack_config, rng)?;
+        let syntax_error_files = write_syntax_error_files(pack_config)?;
+        let long_path_files = write_long_path_file(pack_config)?;
+        let case_twin_files = write_case_twins(pack_config)?;
+        let malformed_files = write_malformed_file(pack_config)?;
+        let doc_files = write_pack_docs(pack_config)?;
+        return Ok(PackResult::Built {
+            files: code_files
+                + odd_files
+                + syntax_error_files
+                + long_path_files
+                + case_twin_files
+                + malformed_files
+                + doc_files,
+        });
+    }
+
+    let team_name = pack_config.team_name();
+    let team_exists = teams.exists(&team_name)?;
+    // A namespace's team is shared by its packs, so only the pack itself can already exist.
+    let exists = match pack_config.namespace {
+        Some(_) => pack_config.pack_path().exists(),
+        None => team_exists,
+    };
+    if exists {
+        return Ok(PackResult::Skipped);
+    }
+    let mut team_files = 0;
+    if !team_exists {
+        write_team_config(pack_config, teams, &team_name)?;
+        team_files += 1;
+    }
+    if let Some(co_owner) = pack_config.co_owner_team_name() {
+        write_team_config(pack_config, teams, &co_owner)?;
+        team_files += 1;
+    }
+    if pack_config.nested_codeowners {
+        write_team_config(
+            pack_config,
+            teams,
+            &nested_codeowners::team_name(pack_config.name),
+        )?;
+        team_files += 1;
     }
-    write_team_config(pack_config, &team_name)?;
     setup_pack_directory(pack_config)?;
-    write_ownership_files(pack_config)?;
-    generate_code_files(pack_config)?;
-    Ok(())
+    let ownership_files = write_ownership_files(pack_config)?;
+    let code_files = generate_code_files(pack_config, rng)?;
+    let edge_case_files = write_edge_cases(pack_config)?;
+    let odd_files = write_odd_files(pack_config, rng)?;
+    let syntax_error_files = write_syntax_error_files(pack_config)?;
+    let long_path_files = write_long_path_file(pack_config)?;
+    let case_twin_files = write_case_twins(pack_config)?;
+    let malformed_files = write_malformed_file(pack_config)?;
+    let doc_files = write_pack_docs(pack_config)?;
+    let nested_files = write_nested_codeowners(pack_config, rng)?;
+    Ok(PackResult::Built {
+        files: team_files
+            + ownership_files
+            + code_files
+            + edge_case_files
+            + odd_files
+            + syntax_error_files
+            + long_path_files
+            + case_twin_files
+            + malformed_files
+            + doc_files
+            + nested_files,
+    })
 }
 
 fn write_code_file(
-    dir_path: &PathBuf,
+    dir_path: &Path,
     name: &str,
     team: &str,
-    annotate: bool,
+    annotation: Option<AnnotationStyle>,
 ) -> anyhow::Result<()> {
     let file_path = dir_path.join(format!("{}.rb", name));
     let mut file_contents = String::new();
-    if annotate {
-        file_contents.push_str(&format!("# @team {}\n", team));
+    if let Some(style) = annotation {
+        file_contents.push_str(&style.header(team));
     }
     file_contents.push_str(&format!("class {}\n{}\nend\n", name, FILE_CONTENTS));
 
@@ -133,6 +1489,8 @@ const CODE_DIRECTORIES: &[&str] = &[
     "t", "u", "v", "w", "x", "y", "z",
 ];
 
+const FILES_PER_DIRECTORY: usize = 30;
+
 const FILE_CONTENTS: &str = "
   def method_1
     puts 'hello'
@@ -163,149 +1521,141 @@ A pointer is pretty much what it says on the tin: it points to some memory addre
 What’s the point of having these two different memory stores? Because of the way the stack works, data access on the stack is fast and easy but requires the data to conform to certain standards. The heap is slower but more versatile and is thus useful when you can’t use the stack.
 ";
 
-const DEFAULT_CODE_OWNERSHIP_YML: &str = "
----
-owned_globs:
-  - \"{app,components,config,frontend,lib,packs,spec}/**/*.{rb,rake,js,jsx,ts,tsx,json,yml}\"
-unowned_globs:
-  - config/code_ownership.yml
-javascript_package_paths:
-  - javascript/packages/**
-vendored_gems_path: gems
-team_file_glob:
-  - config/teams/**/*.yml
-        ";
-const DEFAULT_DEVOPS_TEAM_YML: &str = "
-name: devops
-github:
-  team: '@devops'
-  members:
-  - devops member
-owned_globs:
-- app/**
-- config/application.rb
-- config/boot.rb
-- config/cable.yml
-- config/database.yml
-- config/environment.rb
-- config/environments/development.rb
-- config/environments/production.rb
-- config/environments/test.rb
-- config/importmap.rb
-- config/initializers/assets.rb
-- config/initializers/content_security_policy.rb
-- config/initializers/filter_parameter_logging.rb
-- config/initializers/inflections.rb
-- config/initializers/permissions_policy.rb
-- config/locales/en.yml
-- config/puma.rb
-- config/routes.rb
-- config/storage.yml
-- config/cache.yml
-- config/deploy.yml
-- config/queue.yml
-- config/recurring.yml- config/cache.yml
-- config/deploy.yml
-- config/queue.yml
-- config/recurring.yml
+fn setup_rails_app(config: &Config, shape: CodeOwnershipShape) -> anyhow::Result<()> {
+    if config.uses_embedded_skeleton() {
+        skeleton::materialize(&config.app_dir(), &config.app_name, config.rails_flavor)?;
+    } else {
+        rails_new(config)?;
+    }
 
-";
+    if config.ownership_output.writes_code_ownership() {
+        std::fs::write(
+            config.app_dir().join(code_ownership::CODE_OWNERSHIP_FILE),
+            shape.render(config),
+        )?;
+    }
 
-fn setup_rails_app(config: &Config) -> anyhow::Result<()> {
+    if !config.package_roots.is_empty() {
+        std::fs::write(
+            config.app_dir().join("packwerk.yml"),
+            package_root::packwerk_yml(
+                &config.package_roots,
+                config.pack_namespaces == PackNamespaces::Nested,
+            ),
+        )?;
+    }
+
+    if let Some(ruby_version) = &config.ruby_version {
+        write_ruby_version_files(config, ruby_version)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rails")]
+fn rails_new(config: &Config) -> anyhow::Result<()> {
     Command::new(&config.rails_path)
         .arg("new")
-        .arg(&config.app_dir())
+        .arg(config.app_dir())
         .output()?;
+    Ok(())
+}
 
+#[cfg(not(feature = "rails"))]
+fn rails_new(_config: &Config) -> anyhow::Result<()> {
+    anyhow::bail!("built without the `rails` feature, so only the embedded skeleton is available")
+}
+
+/// Pins Ruby for rbenv/chruby, asdf and mise alike, so scripts that `cd` into the app and run
+/// bundler or packwerk pick up the intended interpreter.
+fn write_ruby_version_files(config: &Config, ruby_version: &str) -> anyhow::Result<()> {
+    let app_dir = config.app_dir();
+    std::fs::write(app_dir.join(".ruby-version"), format!("{}\n", ruby_version))?;
     std::fs::write(
-        config.app_dir().join("config/code_ownership.yml"),
-        DEFAULT_CODE_OWNERSHIP_YML,
+        app_dir.join(".tool-versions"),
+        format!("ruby {}\n", ruby_version),
+    )?;
+    std::fs::write(
+        app_dir.join("mise.toml"),
+        format!("[tools]\nruby = \"{}\"\n", ruby_version),
     )?;
-
     Ok(())
 }
 
-fn setup_dotslash_tools(config: &Config) -> anyhow::Result<()> {
+fn setup_dotslash_tools(
+    config: &Config,
+    downloads: &Downloads,
+    pks_url: &str,
+    codeowners_url: &str,
+) -> anyhow::Result<()> {
     let dotslash_dir = config.app_dir().join(".dotslash");
     std::fs::create_dir_all(&dotslash_dir)?;
 
     // Setup PKS tool
     let pks_path = dotslash_dir.join("pks");
-    let pks_bytes = reqwest::blocking::get(&config.pks_dotslash_path)?.bytes()?;
-    std::fs::write(&pks_path, pks_bytes)?;
-    make_executable(&pks_path)?;
+    if config.pks_dotslash_artifacts.is_empty() {
+        let pks_bytes = downloads.fetch(pks_url)?;
+        std::fs::write(&pks_path, pks_bytes.as_slice())?;
+        make_executable(&pks_path)?;
+    } else {
+        dotslash::write_descriptor(downloads, &pks_path, "pks", &config.pks_dotslash_artifacts)?;
+    }
 
     // Setup codeowners tool
     let codeowners_path = dotslash_dir.join("codeowners-rs");
-    let codeowners_bytes = reqwest::blocking::get(&config.codeowners_dotslash_path)?.bytes()?;
-    std::fs::write(&codeowners_path, codeowners_bytes)?;
-    make_executable(&codeowners_path)?;
+    if config.codeowners_dotslash_artifacts.is_empty() {
+        let codeowners_bytes = downloads.fetch(codeowners_url)?;
+        std::fs::write(&codeowners_path, codeowners_bytes.as_slice())?;
+        make_executable(&codeowners_path)?;
+    } else {
+        dotslash::write_descriptor(
+            downloads,
+            &codeowners_path,
+            "codeowners",
+            &config.codeowners_dotslash_artifacts,
+        )?;
+    }
 
     Ok(())
 }
 
-fn make_executable(path: &PathBuf) -> anyhow::Result<()> {
+fn make_executable(path: &Path) -> anyhow::Result<()> {
     Command::new("chmod").arg("+x").arg(path).output()?;
     Ok(())
 }
 
 fn setup_infra_team(config: &Config) -> anyhow::Result<()> {
-    let team_name = "infra";
-    let team_dir = config.app_dir().join("config/teams").join(team_name);
-    std::fs::create_dir_all(&team_dir)?;
-
-    std::fs::write(
-        team_dir.join(format!("{}.yml", team_name)),
-        DEFAULT_DEVOPS_TEAM_YML,
-    )?;
-
-    Ok(())
-}
-
-enum TeamSetupResult {
-    Success,
-    AlreadyExists,
+    let yaml = config.infra_team.render(config)?;
+    config.team_layout.write(&config.app_dir(), "infra", &yaml)
 }
 
-fn setup_team_directory(
+fn write_team_config(
     pack_config: &PackConfig,
+    teams: &mut Teams,
     team_name: &str,
-) -> anyhow::Result<TeamSetupResult> {
-    let team_dir = pack_config
-        .config
-        .app_dir()
-        .join("config/teams")
-        .join(team_name);
-    if team_dir.exists() {
-        return Ok(TeamSetupResult::AlreadyExists);
+) -> anyhow::Result<()> {
+    let mut team_config = generate_team_config(pack_config, team_name);
+    if pack_config.malformed == Some(MalformedKind::TeamYaml)
+        && team_name == pack_config.team_name()
+    {
+        team_config = malformed::corrupt_team_yaml(&team_config);
     }
-    std::fs::create_dir_all(&team_dir)?;
-    Ok(TeamSetupResult::Success)
-}
-
-fn write_team_config(pack_config: &PackConfig, team_name: &str) -> anyhow::Result<()> {
-    let team_config = generate_team_config(pack_config, team_name);
-    let config_path = pack_config
-        .config
-        .app_dir()
-        .join("config/teams")
-        .join(team_name)
-        .join(format!("{}-team.yml", team_name));
-
-    std::fs::write(config_path, team_config)?;
-    Ok(())
+    teams.write(team_name, &team_config)
 }
 
 fn generate_team_config(pack_config: &PackConfig, team_name: &str) -> String {
     let mut config = format!(
-        "name: {}\ngithub:\n  team: '@{}'\n  members:\n    - {} member\n",
-        team_name, team_name, team_name
+        "name: {}\ngithub:\n  team: '@{}'\n  members:\n",
+        team_name, team_name
     );
+    for member in pack_config.team_members(team_name) {
+        config.push_str(&format!("    - {}\n", member));
+    }
 
     if pack_config.ownership == PackOwnership::TeamConfig {
         config.push_str(&format!(
-            "\nowned_globs:\n  - \"{}/**\"\n",
-            pack_config.relative_pack_path().display()
+            "\nowned_globs:\n  - \"{}\"\n",
+            pack_config.owned_glob(team_name)
         ));
     }
 
@@ -317,37 +1667,245 @@ fn setup_pack_directory(pack_config: &PackConfig) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn write_ownership_files(pack_config: &PackConfig) -> anyhow::Result<()> {
+fn write_ownership_files(pack_config: &PackConfig) -> anyhow::Result<usize> {
     match pack_config.ownership {
         PackOwnership::PackConfig => {
             std::fs::write(
                 pack_config.pack_path().join("package.yml"),
-                format!("owner: {}\n", pack_config.team_name()),
+                package_yml::render(
+                    pack_config.name,
+                    &pack_config.team_name(),
+                    pack_config.owner_in_metadata,
+                    &pack_config.config.package_metadata,
+                    pack_config.product_group,
+                ),
             )?;
+            Ok(1)
         }
         PackOwnership::Directory => {
             std::fs::write(
                 pack_config.pack_path().join(".codeowner"),
                 format!("{}\n", pack_config.team_name()),
             )?;
+            for dir in pack_config.co_owned_directories() {
+                let dir_path = pack_config.pack_path().join("app/services").join(dir);
+                std::fs::create_dir_all(&dir_path)?;
+                std::fs::write(
+                    dir_path.join(".codeowner"),
+                    format!("{}\n", pack_config.directory_team(dir)),
+                )?;
+            }
+            Ok(1 + pack_config.co_owned_directories().len())
         }
-        _ => {}
+        _ => Ok(0),
     }
-    Ok(())
 }
 
-fn generate_code_files(pack_config: &PackConfig) -> anyhow::Result<()> {
+fn write_edge_cases(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let cases = pack_config.edge_cases();
+    if cases.is_empty() {
+        return Ok(0);
+    }
+    let dir = pack_config.edge_case_dir();
+    std::fs::create_dir_all(&dir)?;
+    for case in cases {
+        std::fs::write(
+            dir.join(case.file_name()),
+            // The infra team always exists, so a tool honouring the stray annotations would
+            // silently misattribute the file rather than fail on an unknown team.
+            case.contents(
+                &pack_config.team_name(),
+                &pack_config.config.infra_team.name,
+            ),
+        )?;
+    }
+    Ok(cases.len())
+}
+
+/// Writes the pack's [`OddFileKind`] files next to its ordinary code. Runs after everything
+/// else drew from `rng`, so enabling them leaves the rest of the pack unchanged.
+fn write_odd_files(pack_config: &PackConfig, rng: &mut impl Rng) -> anyhow::Result<usize> {
+    let Some(dir) = pack_config.odd_files_dir else {
+        return Ok(0);
+    };
+    let dir_path = pack_config.pack_path().join("app/services").join(dir);
+    let annotation = (pack_config.ownership == PackOwnership::FileAnnotation)
+        .then(|| AnnotationStyle::Plain.header(&pack_config.directory_team(dir)));
+    for kind in OddFileKind::ALL {
+        let annotation = annotation.as_deref().filter(|_| kind.annotated());
+        std::fs::write(
+            dir_path.join(kind.file_name()),
+            kind.contents(annotation, rng),
+        )?;
+    }
+    Ok(OddFileKind::ALL.len())
+}
+
+fn write_syntax_error_files(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let Some(dir) = pack_config.syntax_errors_dir else {
+        return Ok(0);
+    };
+    let dir_path = pack_config.pack_path().join("app/services").join(dir);
+    let annotation = (pack_config.ownership == PackOwnership::FileAnnotation)
+        .then(|| AnnotationStyle::Plain.header(&pack_config.directory_team(dir)));
+    for kind in SyntaxErrorKind::ALL {
+        std::fs::write(
+            dir_path.join(kind.file_name()),
+            kind.contents(annotation.as_deref()),
+        )?;
+    }
+    Ok(SyntaxErrorKind::ALL.len())
+}
+
+fn write_long_path_file(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let Some(path) = pack_config.long_path_file()? else {
+        return Ok(0);
+    };
+    let path = pack_config.config.app_dir().join(path);
+    std::fs::create_dir_all(path.parent().expect("long paths are below the pack"))?;
+    let mut contents = String::new();
+    if pack_config.ownership == PackOwnership::FileAnnotation {
+        contents.push_str(&AnnotationStyle::Plain.header(&pack_config.team_name()));
+    }
+    contents.push_str("class VeryLongFileName\nend\n");
+    std::fs::write(path, contents)?;
+    Ok(1)
+}
+
+/// Writes the pack's broken `package.yml` or `code_ownership.yml` entry. Corrupt team YAML is
+/// written by [`write_team_config`] instead. The entry is only appended once, so a resumed
+/// pack doesn't add it twice.
+fn write_malformed_file(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let pack_path = pack_config.relative_pack_path();
+    match pack_config.malformed {
+        Some(MalformedKind::PackageYml) => {
+            let owner = (pack_config.ownership == PackOwnership::PackConfig)
+                .then(|| pack_config.team_name());
+            std::fs::write(
+                pack_config.pack_path().join("package.yml"),
+                malformed::truncated_package_yml(&pack_path, owner.as_deref()),
+            )?;
+            Ok(usize::from(owner.is_none()))
+        }
+        Some(MalformedKind::CodeOwnershipEntry) => {
+            let path = pack_config
+                .config
+                .app_dir()
+                .join(code_ownership::CODE_OWNERSHIP_FILE);
+            let mut contents = std::fs::read_to_string(&path)?;
+            let entry = malformed::code_ownership_entry(&pack_path);
+            if !contents.contains(&entry) {
+                contents.push_str(&entry);
+                std::fs::write(path, contents)?;
+            }
+            Ok(0)
+        }
+        Some(MalformedKind::TeamYaml) | None => Ok(0),
+    }
+}
+
+/// Writes each of the pack's nested `.codeowner` files along with some code for it to own.
+fn write_nested_codeowners(pack_config: &PackConfig, rng: &mut impl Rng) -> anyhow::Result<usize> {
+    let directories = pack_config.nested_directories();
+    for (directory, team) in &directories {
+        let dir_path = pack_config.config.app_dir().join(directory);
+        std::fs::create_dir_all(&dir_path)?;
+        std::fs::write(dir_path.join(".codeowner"), format!("{}\n", team))?;
+        for _ in 0..nested_codeowners::FILES_PER_NESTED_DIR {
+            write_code_file(&dir_path, &random_name(rng), team, None)?;
+        }
+    }
+    Ok(directories.len() * (1 + nested_codeowners::FILES_PER_NESTED_DIR))
+}
+
+fn write_pack_docs(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    if !pack_config.config.pack_docs {
+        return Ok(0);
+    }
+    let annotation = (pack_config.ownership == PackOwnership::FileAnnotation)
+        .then(|| format!("<!-- @team {} -->\n", pack_config.team_name()));
+    pack_docs::write(
+        &pack_config.pack_path(),
+        pack_config.name,
+        &pack_config.relative_pack_path(),
+        annotation.as_deref(),
+    )
+}
+
+/// Writes each pair from [`PackConfig::case_twin_pairs`], skipping the second file of a pair
+/// on case-insensitive filesystems where it would overwrite the first.
+fn write_case_twins(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let app_dir = pack_config.config.app_dir();
+    let mut contents = String::new();
+    if pack_config.ownership == PackOwnership::FileAnnotation {
+        contents.push_str(&AnnotationStyle::Plain.header(&pack_config.team_name()));
+    }
+    contents.push_str("class CaseTwin\nend\n");
+    let mut files = 0;
+    for (original, twin, _) in pack_config.case_twin_pairs() {
+        let wrote_twin =
+            case_twins::write_twin(&app_dir.join(original), &app_dir.join(twin), &contents)?;
+        files += 1 + usize::from(wrote_twin);
+    }
+    Ok(files)
+}
+
+/// The code of a [`Preset::Coverage`] pack: one file named after the pack, plus the extension
+/// and violation files that pack's ownership calls for.
+fn write_coverage_files(pack_config: &PackConfig) -> anyhow::Result<usize> {
+    let dir_path = pack_config.pack_path().join("app/services/a");
+    std::fs::create_dir_all(&dir_path)?;
+    let annotate = pack_config.ownership == PackOwnership::FileAnnotation;
+    write_code_file(
+        &dir_path,
+        pack_config.name,
+        &pack_config.team_name(),
+        annotate.then_some(AnnotationStyle::Plain),
+    )?;
+    let mut files = 1;
+    if pack_config.ownership == PackOwnership::Directory {
+        for (file_name, contents) in preset::COVERAGE_EXTENSION_FILES {
+            std::fs::write(dir_path.join(file_name), contents)?;
+        }
+        files += preset::COVERAGE_EXTENSION_FILES.len();
+    }
+    for violation in pack_config.violations() {
+        let (name, team) = match violation.kind {
+            ViolationKind::MultipleOwners => (
+                "multiple_owners",
+                pack_config.config.infra_team.name.as_str(),
+            ),
+            ViolationKind::UnknownTeam => ("unknown_team", "unknown-team"),
+            // The pack's own file, already written without an owner.
+            ViolationKind::Unowned => continue,
+        };
+        write_code_file(&dir_path, name, team, Some(AnnotationStyle::Plain))?;
+        files += 1;
+    }
+    Ok(files)
+}
+
+fn generate_code_files(pack_config: &PackConfig, rng: &mut impl Rng) -> anyhow::Result<usize> {
+    if pack_config.config.preset == Some(Preset::Coverage) {
+        return write_coverage_files(pack_config);
+    }
     let annotate = pack_config.ownership == PackOwnership::FileAnnotation;
-    let team_name = pack_config.team_name();
 
     for dir in CODE_DIRECTORIES {
         let dir_path = pack_config.pack_path().join("app/services").join(dir);
         std::fs::create_dir_all(&dir_path)?;
+        let team_name = pack_config.directory_team(dir);
 
-        for _ in 0..30 {
-            write_code_file(&dir_path, &random_name(), &team_name, annotate)?;
+        for _ in 0..FILES_PER_DIRECTORY {
+            let name = random_name(rng);
+            let annotation = match (annotate, pack_config.config.varied_annotations) {
+                (false, _) => None,
+                (true, false) => Some(AnnotationStyle::Plain),
+                (true, true) => Some(AnnotationStyle::random(rng)),
+            };
+            write_code_file(&dir_path, &name, &team_name, annotation)?;
         }
     }
 
-    Ok(())
+    Ok(CODE_DIRECTORIES.len() * FILES_PER_DIRECTORY)
 }
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+/// The longest file or directory name common filesystems accept (`NAME_MAX`).
+const MAX_COMPONENT_LEN: usize = 255;
+/// The longest path Linux accepts in a single call (`PATH_MAX`, counting the trailing NUL).
+/// Going further would mean walking down with `openat`, which std doesn't expose, so
+/// generated paths stop one byte short of it.
+const MAX_PATH_LEN: usize = 4096;
+
+/// A path below `dir` whose absolute form is as long as the OS allows: directories named with
+/// [`MAX_COMPONENT_LEN`] characters, ending in a Ruby file whose name is just as long.
+/// Returns `None` when `dir` is already too deep to fit a file below it.
+pub(crate) fn longest_file_path(dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+    let absolute_len = std::path::absolute(dir)?.as_os_str().len();
+    // Every component below `dir` costs a separator too.
+    let Some(mut budget) = (MAX_PATH_LEN - 1).checked_sub(absolute_len) else {
+        return Ok(None);
+    };
+    let mut path = PathBuf::new();
+    let mut index = 0;
+    while budget > 2 * (MAX_COMPONENT_LEN + 1) {
+        path.push(component_name(
+            "deeply_nested_directory",
+            index,
+            MAX_COMPONENT_LEN,
+        ));
+        budget -= MAX_COMPONENT_LEN + 1;
+        index += 1;
+    }
+    if budget > MAX_COMPONENT_LEN + 1 {
+        // A budget one over what a file can take still needs a directory, which leaves the
+        // file a character short.
+        let dir_len = (budget - MAX_COMPONENT_LEN - 2).max(1);
+        path.push(component_name("deeply_nested_directory", index, dir_len));
+        budget -= dir_len + 1;
+    }
+    let file_len = budget.saturating_sub(1);
+    if file_len < "x.rb".len() {
+        return Ok(None);
+    }
+    path.push(format!(
+        "{}.rb",
+        component_name("very_long_file_name", 0, file_len - ".rb".len())
+    ));
+    Ok(Some(path))
+}
+
+/// `stem` and `index` repeated out to exactly `len` characters.
+fn component_name(stem: &str, index: usize, len: usize) -> String {
+    format!("{}_{}_", stem, index)
+        .chars()
+        .cycle()
+        .take(len)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An absolute directory path `len` bytes long, below `/` so joining adds a separator.
+    fn dir_of_len(len: usize) -> PathBuf {
+        PathBuf::from(format!("/{}", "d".repeat(len - 1)))
+    }
+
+    #[test]
+    fn paths_stop_one_byte_short_of_path_max() {
+        for dir_len in 2..MAX_PATH_LEN {
+            let dir = dir_of_len(dir_len);
+            let Some(path) = longest_file_path(&dir).unwrap() else {
+                continue;
+            };
+            assert_eq!(dir.join(&path).as_os_str().len(), MAX_PATH_LEN - 1);
+            assert!(path
+                .components()
+                .all(|component| component.as_os_str().len() <= MAX_COMPONENT_LEN));
+            assert_eq!(path.extension().unwrap(), "rb");
+        }
+    }
+
+    #[test]
+    fn file_names_take_all_the_room_left() {
+        let path = longest_file_path(&dir_of_len(MAX_PATH_LEN - 1 - (MAX_COMPONENT_LEN + 1)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.components().count(), 1);
+        assert_eq!(path.as_os_str().len(), MAX_COMPONENT_LEN);
+    }
+
+    #[test]
+    fn dirs_too_deep_for_a_file_get_none() {
+        assert_eq!(
+            longest_file_path(&dir_of_len(MAX_PATH_LEN - 5)).unwrap(),
+            None
+        );
+        assert_eq!(
+            longest_file_path(&dir_of_len(MAX_PATH_LEN + 10)).unwrap(),
+            None
+        );
+        assert!(longest_file_path(&dir_of_len(MAX_PATH_LEN - 6))
+            .unwrap()
+            .is_some());
+    }
+}
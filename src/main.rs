@@ -1,17 +1,1112 @@
-use random_rails_generator::{build_app, Config};
+use std::{ffi::OsString, path::PathBuf, process::ExitCode, time::Duration};
 
-fn main() -> anyhow::Result<()> {
+use anyhow::Context;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use random_rails_generator::{
+    build_app_with_observer, compare, run_bench, run_case, run_fuzz, touch, watch, Baselines,
+    BenchConfig, BenchResult, CancellationToken, Cancelled, Change, CodeOwnershipShape,
+    CodeownersFile, Comparison, Config, DotslashArtifact, FileTimes, Finding, FuzzCase, FuzzConfig,
+    GenerationEvent, InfraTeam, Metrics, OnExists, OwnershipOutput, PackNamespaces,
+    PackageMetadata, PackageOwnerKey, PackageRoot, Preset, RailsFlavor, RailsSource, TableFormat,
+    TeamLayout, TeamSize, ToolOutcome, WatchConfig,
+};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_CODEOWNERS_URL: &str =
+    "https://github.com/rubyatscale/codeowners-rs/releases/download/v0.2.1/codeowners";
+const DEFAULT_PKS_URL: &str = "https://github.com/rubyatscale/pks/releases/download/v0.2.23/pks";
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Generate a random Rails monolith for exercising ownership tooling",
+    after_help = "Exit codes:\n  0    success\n  1    the command failed\n  2    invalid arguments\n  3    a tool run crashed\n  4    a tool run reported problems with the generated app, or named a wrong owner\n  5    a benchmark regressed against its baseline\n  130  cancelled",
+    args_conflicts_with_subcommands = true,
+    args_override_self = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// YAML or JSON file of options keyed like the flags, with underscores, e.g.
+    /// `num_packages: 10`. Flags on the command line take precedence; lists from both are
+    /// combined. `config-schema` prints the file's schema.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value = "/home/perryhertler/.local/share/mise/installs/ruby/3.3.5/bin/rails"
+    )]
+    rails_path: String,
+    #[arg(long, default_value = "/home/perryhertler/Software/tmp/gen-play")]
+    base_dir: String,
+    #[arg(long, default_value = "my_app")]
+    app_name: String,
+    #[arg(long, default_value_t = 1000)]
+    num_packages: usize,
+    #[arg(long, default_value = DEFAULT_CODEOWNERS_URL)]
+    codeowners_dotslash_path: String,
+    #[arg(long, default_value = DEFAULT_PKS_URL)]
+    pks_dotslash_path: String,
+    /// Seed for every random choice. A random seed is used when omitted.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Continue an interrupted run from the checkpoint in the app directory.
+    #[arg(long)]
+    resume: bool,
+    /// What to do when the app directory already exists: fail, overwrite or merge.
+    #[arg(long, default_value = "fail")]
+    on_exists: OnExists,
+    /// `json` prints every progress event and the final result as newline-delimited JSON.
+    #[arg(long, value_enum, default_value_t = Output::Human)]
+    output: Output,
+    /// Validate the finished app with the downloaded codeowners and pks binaries.
+    #[arg(long)]
+    run_tools: bool,
+    /// Shell command to run in the finished app, with RANDOM_RAILS_GENERATOR_APP_DIR,
+    /// RANDOM_RAILS_GENERATOR_MANIFEST and RANDOM_RAILS_GENERATOR_SEED set. Repeat to run
+    /// several in order; generation fails if one does.
+    #[arg(long = "post-generation-hook", value_name = "COMMAND")]
+    post_generation_hooks: Vec<String>,
+    /// Write the skeleton bundled into this binary instead of running `rails new`.
+    #[arg(long)]
+    embedded_skeleton: bool,
+    /// Rails layout for --embedded-skeleton: 6.1, 7.0, 7.2 or 8.0.
+    #[arg(long, default_value = "8.0")]
+    rails_flavor: RailsFlavor,
+    /// Generate one app per comma-separated entry, each a flavor (6.1, 7.0, 7.2, 8.0) or a path
+    /// to a `rails` binary.
+    #[arg(long, value_delimiter = ',')]
+    matrix: Vec<RailsSource>,
+    /// How many --matrix apps to build at once. Defaults to the number of CPUs.
+    #[arg(long)]
+    jobs: Option<usize>,
+    /// Pin this Ruby version in .ruby-version, .tool-versions and mise.toml.
+    #[arg(long)]
+    ruby_version: Option<String>,
+    /// Write .dotslash/pks as a dotslash descriptor with this `<platform>=<url>` artifact.
+    /// Repeat once per platform.
+    #[arg(long = "pks-dotslash-artifact")]
+    pks_dotslash_artifacts: Vec<DotslashArtifact>,
+    /// Write .dotslash/codeowners-rs as a dotslash descriptor with this `<platform>=<url>`
+    /// artifact. Repeat once per platform.
+    #[arg(long = "codeowners-dotslash-artifact")]
+    codeowners_dotslash_artifacts: Vec<DotslashArtifact>,
+    /// Download pks from the GitHub release matching this version instead of
+    /// --pks-dotslash-path: `latest`, a tag, or a pattern like `v0.2.*`.
+    #[arg(long)]
+    pks_version: Option<String>,
+    /// Download codeowners-rs from the GitHub release matching this version instead of
+    /// --codeowners-dotslash-path.
+    #[arg(long)]
+    codeowners_version: Option<String>,
+    /// Extra PEM root certificates to trust for downloads. Proxies are taken from
+    /// HTTPS_PROXY/NO_PROXY.
+    #[arg(long)]
+    ca_bundle: Option<String>,
+    /// Disable TLS certificate verification for downloads.
+    #[arg(long)]
+    insecure_tls: bool,
+    /// Percentage of packs split between two teams.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    co_owned_percent: u8,
+    /// Percentage of packs generated with no owner at all.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    ownerless_percent: u8,
+    /// Percentage of pack teams renamed, with the old names written to team-aliases.yml.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    team_rename_percent: u8,
+    /// GitHub members per team: `N`, `MIN..MAX`, or `MIN..MAX:long-tail` for mostly small
+    /// teams with a few large ones.
+    #[arg(long, default_value = "1..8")]
+    team_size: TeamSize,
+    /// Team YAML layout: nested (a directory per team), flat, or combined (one multi-document
+    /// config/teams.yml).
+    #[arg(long, default_value = "nested")]
+    team_layout: TeamLayout,
+    /// code_ownership.yml template: standard, minimal, extra-keys, alternate, or random to
+    /// pick one from the seed.
+    #[arg(long, default_value = "standard")]
+    code_ownership_shape: CodeOwnershipShape,
+    /// Configure ownership through code-ownership (config/code_ownership.yml), codeowners (a
+    /// hand-written .github/CODEOWNERS) or both.
+    #[arg(long, default_value = "code-ownership")]
+    ownership_output: OwnershipOutput,
+    /// Comma-separated metadata sections for package.yml files: protections, custom-keys and
+    /// product-group.
+    #[arg(long, value_delimiter = ',')]
+    package_metadata: Vec<PackageMetadata>,
+    /// Where package.yml declares the owner: top-level, metadata, or random to pick per pack.
+    #[arg(long, default_value = "top-level")]
+    package_owner_key: PackageOwnerKey,
+    /// Comma-separated directories to spread packs across, listed in packwerk.yml's
+    /// package_paths: packs, components, gems and domains. Defaults to packs alone.
+    #[arg(long, value_delimiter = ',')]
+    package_roots: Vec<PackageRoot>,
+    /// Group packs into namespaces owned by one team each: flat, prefixed
+    /// (packs/payments_refunds) or nested (packs/payments/refunds).
+    #[arg(long, default_value = "flat")]
+    pack_namespaces: PackNamespaces,
+    /// Write generator-ownership.csv or .parquet next to the manifest, listing every file with
+    /// the team meant to own it and how.
+    #[arg(long)]
+    ownership_table: Option<TableFormat>,
+    /// Write a CODEOWNERS file in this format (github, gitlab or bitbucket), at the host's
+    /// usual location or at `<format>=<path>`. Repeat for more. Defaults to github.
+    #[arg(long = "codeowners-file")]
+    codeowners_files: Vec<CodeownersFile>,
+    /// Add this glob to owned_globs in code_ownership.yml. Repeat for more.
+    #[arg(long = "owned-glob")]
+    extra_owned_globs: Vec<String>,
+    /// Add this glob to unowned_globs in code_ownership.yml. Repeat for more.
+    #[arg(long = "unowned-glob")]
+    extra_unowned_globs: Vec<String>,
+    /// Put `# @team` annotations after magic comments, shebangs and blank lines instead of
+    /// always on the first line.
+    #[arg(long)]
+    varied_annotations: bool,
+    /// Add files with stray, conflicting and misplaced `@team` annotations to annotation-owned
+    /// packs. The manifest records whether tools should ignore them or report an error.
+    #[arg(long)]
+    pathological_annotations: bool,
+    /// Percentage of packs that get a multi-megabyte Ruby file, a binary blob and an empty
+    /// file among their code.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    odd_files_percent: u8,
+    /// Percentage of packs that get a file whose absolute path is as long as the OS allows.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    long_paths_percent: u8,
+    /// Percentage of packs that get files and a twin pack differing only by case, where the
+    /// filesystem allows it.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    case_twins_percent: u8,
+    /// Percentage of packs that break their team YAML, their package.yml or an entry in
+    /// code_ownership.yml. The manifest lists each broken file.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    malformed_percent: u8,
+    /// Percentage of directory-owned packs with .codeowner files in nested subdirectories,
+    /// handing a subtree to another team and a subtree of that back.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    nested_codeowners_percent: u8,
+    /// Percentage of packs that get Ruby files with syntax errors: an unbalanced end, a
+    /// missing one and stray bytes. The manifest lists each of them.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+    syntax_error_percent: u8,
+    /// Set every generated file's mtime to this many seconds since the Unix epoch, or to
+    /// `seed` for per-file times derived from the seed.
+    #[arg(long)]
+    mtime: Option<FileTimes>,
+    /// Give every pack a README.md and a docs/ folder owned by the pack's team.
+    #[arg(long)]
+    pack_docs: bool,
+    /// Write this many directories of models, controllers and services outside the packs,
+    /// owned through annotations or team globs.
+    #[arg(long, default_value_t = 0)]
+    app_code_dirs: usize,
+    /// Write spec/spec_helper.rb, spec/rails_helper.rb and spec/support, owned by the infra
+    /// team or through annotations.
+    #[arg(long)]
+    spec_support: bool,
+    /// Files to write to node_modules, vendor/bundle, tmp/cache and log, which tools should
+    /// skip.
+    #[arg(long, default_value_t = 0)]
+    noise_files: usize,
+    /// Team YAML for the team that owns everything outside the packs, in place of the default
+    /// devops team. Other keys in the file are kept.
+    #[arg(long)]
+    infra_team: Option<PathBuf>,
+    /// Name for the default infra team instead of devops.
+    #[arg(long, conflicts_with = "infra_team")]
+    infra_team_name: Option<String>,
+    /// Write a self-contained, reproducible fixture to this directory, e.g.
+    /// tests/fixtures/<name>: embedded skeleton, no downloads, no absolute paths. Replaces
+    /// --base-dir and --app-name and needs --seed.
+    #[arg(long, conflicts_with_all = ["base_dir", "app_name"])]
+    export_fixture: Option<PathBuf>,
+    /// Apply a bundle of options over the others. `coverage` is the smallest app with every
+    /// ownership type, owned file extension and violation once; it sets --num-packages itself.
+    #[arg(long)]
+    preset: Option<Preset>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Time the codeowners and pks binaries against a generated app, optionally storing the
+    /// results as a baseline or checking them against one.
+    Bench(BenchArgs),
+    /// Generate small apps with randomly sampled options, run the tools against each, and
+    /// report every crash, failed validation and wrong owner with the seed that reproduces it.
+    Fuzz(FuzzArgs),
+    /// Change a generated app in place.
+    Mutate {
+        #[command(subcommand)]
+        mutation: Mutation,
+    },
+    /// Print a completion script for `shell` to stdout, e.g. to source from `~/.bashrc`.
+    Completions { shell: Shell },
+    /// Print the JSON schema of --config files, for editors to validate them against.
+    ConfigSchema,
+}
+
+#[derive(Debug, Subcommand)]
+enum Mutation {
+    /// Update the mtimes of a random subset of files without changing their contents, to
+    /// measure how tools' mtime-based caches handle false invalidations.
+    Touch(TouchArgs),
+    /// Keep adding, editing and moving Ruby files on an interval until interrupted, like a
+    /// developer working in the app, for soak-testing tools that watch it.
+    Watch(WatchArgs),
+}
+
+#[derive(Debug, Args)]
+struct TouchArgs {
+    /// A previously generated app.
+    app_dir: PathBuf,
+    /// Percentage of the app's files to touch. .dotslash and tmp are left alone.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    percent: u8,
+    /// Seed for picking the files. A random seed is used when omitted.
+    #[arg(long)]
+    seed: Option<u64>,
+    #[arg(long, value_enum, default_value_t = Output::Human)]
+    output: Output,
+}
+
+#[derive(Debug, Args)]
+struct WatchArgs {
+    /// A previously generated app.
+    app_dir: PathBuf,
+    /// Milliseconds between changes.
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
+    /// Seed for the changes. A random seed is used when omitted.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Stop after this many changes.
+    #[arg(long)]
+    max_changes: Option<usize>,
+    #[arg(long, value_enum, default_value_t = Output::Human)]
+    output: Output,
+}
+
+#[derive(Debug, Args)]
+struct BenchArgs {
+    /// A previously generated app.
+    app_dir: PathBuf,
+    /// Timed runs per tool. Medians are stored and compared.
+    #[arg(long, default_value_t = 5)]
+    runs: usize,
+    /// JSON file holding baselines, keyed by tool, tool version and fixture seed.
+    #[arg(long, default_value = "bench-baselines.json")]
+    baselines: PathBuf,
+    /// Store this run's results as baselines.
+    #[arg(long)]
+    save: bool,
+    /// Compare against the stored baselines for the same fixture seed and exit with 5 when a
+    /// tool is slower by more than --threshold.
+    #[arg(long)]
+    compare: bool,
+    /// Compare against this tool version's baseline instead of the most recent one.
+    #[arg(long, requires = "compare")]
+    baseline_version: Option<String>,
+    /// Slowdown, in percent of the baseline median, that counts as a regression.
+    #[arg(long, default_value_t = 10.0)]
+    threshold: f64,
+    /// Record `<tool>=<version>` instead of the version in the app's manifest, e.g. for a
+    /// locally built binary copied into .dotslash.
+    #[arg(long = "tool-version", value_parser = parse_tool_version)]
+    tool_versions: Vec<(String, String)>,
+    /// Let hyperfine take the measurements, optionally naming its binary. Results are stored
+    /// and compared exactly like the built-in timings.
+    #[arg(long, num_args = 0..=1, default_missing_value = "hyperfine")]
+    hyperfine: Option<String>,
+    #[arg(long, value_enum, default_value_t = Output::Human)]
+    output: Output,
+}
+
+#[derive(Debug, Args)]
+struct FuzzArgs {
+    /// Where the sampled apps are written, each as fuzz-<seed>.
+    #[arg(long, default_value = "fuzz")]
+    base_dir: String,
+    #[arg(long, default_value_t = 20)]
+    iterations: usize,
+    /// Seed for the sequence of sampled apps. A random seed is used when omitted.
+    #[arg(long, conflicts_with = "reproduce")]
+    seed: Option<u64>,
+    /// Regenerate and recheck only the app a finding reported this seed for.
+    #[arg(long, conflicts_with = "iterations")]
+    reproduce: Option<u64>,
+    /// Most packs in a sampled app.
+    #[arg(long, default_value_t = 8)]
+    max_packages: usize,
+    /// Keep the apps that had no findings too.
+    #[arg(long)]
+    keep_apps: bool,
+    #[arg(long, default_value = DEFAULT_CODEOWNERS_URL)]
+    codeowners_dotslash_path: String,
+    #[arg(long, default_value = DEFAULT_PKS_URL)]
+    pks_dotslash_path: String,
+    #[arg(long, value_enum, default_value_t = Output::Human)]
+    output: Output,
+}
+
+fn parse_tool_version(s: &str) -> anyhow::Result<(String, String)> {
+    let Some((tool, version)) = s.split_once('=') else {
+        anyhow::bail!("expected <tool>=<version>, got '{}'", s);
+    };
+    Ok((tool.to_string(), version.to_string()))
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Output {
+    Human,
+    Json,
+}
+
+impl Output {
+    fn event(self, event: &GenerationEvent) {
+        match self {
+            Output::Human => print_progress(event),
+            Output::Json => print_json(event),
+        }
+    }
+
+    fn failure(self, err: &anyhow::Error) -> Exit {
+        match (err.downcast_ref::<Cancelled>(), self) {
+            (Some(cancelled), Output::Human) => eprintln!(
+                "Cancelled: {} of {} packs were fully written. Rerun with --resume to continue.",
+                cancelled.completed_packs, cancelled.total_packs
+            ),
+            (Some(cancelled), Output::Json) => print_json(&Failure::Cancelled(cancelled)),
+            (None, Output::Human) => eprintln!("Error: {:?}", err),
+            (None, Output::Json) => print_json(&Failure::Error {
+                message: format!("{:#}", err),
+            }),
+        }
+        if err.is::<Cancelled>() {
+            Exit::Cancelled
+        } else {
+            Exit::GenerationFailed
+        }
+    }
+}
+
+/// Process exit codes, so CI jobs can branch on the outcome without parsing output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    Success = 0,
+    GenerationFailed = 1,
+    ToolCrashed = 3,
+    OutputMismatch = 4,
+    PerfRegression = 5,
+    Cancelled = 130,
+}
+
+impl Exit {
+    /// Same ranking as [`Exit::after_tool_run`]: wrong owners count as reported problems.
+    fn after_finding(self, finding: &Finding) -> Self {
+        match (self, finding) {
+            (_, Finding::ToolCrashed { .. }) => Exit::ToolCrashed,
+            (Exit::Success, _) => Exit::OutputMismatch,
+            (exit, _) => exit,
+        }
+    }
+
+    /// A crashed tool outranks one that merely reported problems.
+    fn after_tool_run(self, outcome: &ToolOutcome) -> Self {
+        match (self, outcome) {
+            (_, ToolOutcome::Crashed { .. }) => Exit::ToolCrashed,
+            (Exit::Success, ToolOutcome::Failed { .. }) => Exit::OutputMismatch,
+            (exit, _) => exit,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match with_config_file(std::env::args_os().collect()) {
+        Ok(args) => args,
+        Err(err) => return ExitCode::from(Output::Human.failure(&err) as u8),
+    };
+    let mut cli = Cli::parse_from(args);
+    let exit = match cli.command.take() {
+        Some(Command::Bench(args)) => {
+            let output = args.output;
+            bench(args).unwrap_or_else(|err| output.failure(&err))
+        }
+        Some(Command::Fuzz(args)) => {
+            let output = args.output;
+            fuzz(args).unwrap_or_else(|err| output.failure(&err))
+        }
+        Some(Command::Mutate {
+            mutation: Mutation::Touch(args),
+        }) => {
+            let output = args.output;
+            mutate_touch(args).unwrap_or_else(|err| output.failure(&err))
+        }
+        Some(Command::Mutate {
+            mutation: Mutation::Watch(args),
+        }) => {
+            let output = args.output;
+            mutate_watch(args).unwrap_or_else(|err| output.failure(&err))
+        }
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "random-rails-generator",
+                &mut std::io::stdout(),
+            );
+            Exit::Success
+        }
+        Some(Command::ConfigSchema) => {
+            print_json(&ConfigFile::schema());
+            Exit::Success
+        }
+        None => {
+            let output = cli.output;
+            run(cli).unwrap_or_else(|err| output.failure(&err))
+        }
+    };
+    ExitCode::from(exit as u8)
+}
+
+/// `args` with the options of the --config file among them, if there is one, inserted before
+/// the command line's own so those override them.
+fn with_config_file(mut args: Vec<OsString>) -> anyhow::Result<Vec<OsString>> {
+    let Some(path) = config_file_path(&args) else {
+        return Ok(args);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    let config: ConfigFile = serde_yaml::from_str(&contents)
+        .with_context(|| format!("failed to parse config file {}", path.display()))?;
+    args.splice(1..1, config.args());
+    Ok(args)
+}
+
+/// The --config file given among the generate command's flags. Scanning stops at the first
+/// positional argument, since that's a subcommand and everything after it is its own.
+fn config_file_path(args: &[OsString]) -> Option<PathBuf> {
+    let command = Cli::command();
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let arg = arg.to_str()?;
+        if arg == "--" || !arg.starts_with('-') {
+            return None;
+        }
+        let Some(long) = arg.strip_prefix("--") else {
+            continue;
+        };
+        let (name, value) = match long.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (long, None),
+        };
+        if name == "config" {
+            return value
+                .map(PathBuf::from)
+                .or_else(|| args.next().map(PathBuf::from));
+        }
+        let takes_value = command
+            .get_arguments()
+            .any(|arg| arg.get_long() == Some(name) && arg.get_action().takes_values());
+        if takes_value && value.is_none() {
+            args.next();
+        }
+    }
+    None
+}
+
+/// Declares [`ConfigFile`] with an optional field per generate flag, named after the flag's
+/// id, and derives both the flags a file stands for and its JSON schema from the field types.
+macro_rules! config_file {
+    ($($field:ident: $ty:ty,)*) => {
+        /// Options read from a --config file. They're handed to clap as the flags they mirror,
+        /// so values are parsed and checked the same way as on the command line.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct ConfigFile {
+            $($field: Option<$ty>,)*
+        }
+
+        impl ConfigFile {
+            fn args(self) -> Vec<OsString> {
+                let command = Cli::command();
+                let mut args = Vec::new();
+                $(if let Some(value) = self.$field {
+                    value.push_args(&flag(&command, stringify!($field)), &mut args);
+                })*
+                args
+            }
+
+            /// JSON schema of --config files, for editors to validate them against.
+            fn schema() -> serde_json::Value {
+                let command = Cli::command();
+                let properties = serde_json::Map::from_iter([$((
+                    stringify!($field).to_string(),
+                    option_schema(&command, stringify!($field), <$ty>::schema()),
+                ),)*]);
+                serde_json::json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "title": "random-rails-generator config",
+                    "type": "object",
+                    "properties": properties,
+                    "additionalProperties": false,
+                })
+            }
+        }
+    };
+}
+
+config_file! {
+    rails_path: Text,
+    base_dir: Text,
+    app_name: Text,
+    num_packages: usize,
+    codeowners_dotslash_path: Text,
+    pks_dotslash_path: Text,
+    seed: u64,
+    resume: bool,
+    on_exists: Text,
+    output: Text,
+    run_tools: bool,
+    post_generation_hooks: Vec<Text>,
+    embedded_skeleton: bool,
+    rails_flavor: Text,
+    matrix: Vec<Text>,
+    jobs: usize,
+    ruby_version: Text,
+    pks_dotslash_artifacts: Vec<Text>,
+    codeowners_dotslash_artifacts: Vec<Text>,
+    pks_version: Text,
+    codeowners_version: Text,
+    ca_bundle: Text,
+    insecure_tls: bool,
+    co_owned_percent: u8,
+    ownerless_percent: u8,
+    team_rename_percent: u8,
+    team_size: Text,
+    team_layout: Text,
+    code_ownership_shape: Text,
+    ownership_output: Text,
+    package_metadata: Vec<Text>,
+    package_owner_key: Text,
+    package_roots: Vec<Text>,
+    pack_namespaces: Text,
+    ownership_table: Text,
+    codeowners_files: Vec<Text>,
+    extra_owned_globs: Vec<Text>,
+    extra_unowned_globs: Vec<Text>,
+    varied_annotations: bool,
+    pathological_annotations: bool,
+    odd_files_percent: u8,
+    long_paths_percent: u8,
+    case_twins_percent: u8,
+    malformed_percent: u8,
+    nested_codeowners_percent: u8,
+    syntax_error_percent: u8,
+    mtime: Text,
+    pack_docs: bool,
+    app_code_dirs: usize,
+    spec_support: bool,
+    noise_files: usize,
+    infra_team: Text,
+    infra_team_name: Text,
+    export_fixture: Text,
+    preset: Text,
+}
+
+/// A [`ConfigFile`] value: how it's written as flags and described in the schema.
+trait ConfigValue {
+    fn schema() -> serde_json::Value;
+    fn push_args(self, flag: &str, args: &mut Vec<OsString>);
+}
+
+impl ConfigValue for bool {
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "type": "boolean" })
+    }
+
+    fn push_args(self, flag: &str, args: &mut Vec<OsString>) {
+        if self {
+            args.push(flag.into());
+        }
+    }
+}
+
+macro_rules! integer_config_value {
+    ($($ty:ty),*) => {
+        $(impl ConfigValue for $ty {
+            fn schema() -> serde_json::Value {
+                serde_json::json!({ "type": "integer", "minimum": 0 })
+            }
+
+            fn push_args(self, flag: &str, args: &mut Vec<OsString>) {
+                args.extend([flag.into(), self.to_string().into()]);
+            }
+        })*
+    };
+}
+
+integer_config_value!(u8, u64, usize);
+
+impl<T: ConfigValue> ConfigValue for Vec<T> {
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "type": "array", "items": T::schema() })
+    }
+
+    fn push_args(self, flag: &str, args: &mut Vec<OsString>) {
+        for value in self {
+            value.push_args(flag, args);
+        }
+    }
+}
+
+/// A value clap parses from text. Numbers are accepted too, so `rails_flavor: 7.2` or
+/// `mtime: 1700000000` don't need quoting.
+struct Text(String);
+
+impl<'de> Deserialize<'de> for Text {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_yaml::Value::deserialize(deserializer)? {
+            serde_yaml::Value::String(value) => Ok(Text(value)),
+            serde_yaml::Value::Number(value) => Ok(Text(value.to_string())),
+            value => Err(serde::de::Error::custom(format!(
+                "expected a string or number, got {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+impl ConfigValue for Text {
+    fn schema() -> serde_json::Value {
+        serde_json::json!({ "type": ["string", "number"] })
+    }
+
+    fn push_args(self, flag: &str, args: &mut Vec<OsString>) {
+        args.extend([flag.into(), self.0.into()]);
+    }
+}
+
+/// The generate command's flag with this id, e.g. `--owned-glob` for `extra_owned_globs`.
+fn flag(command: &clap::Command, id: &str) -> String {
+    let arg = generate_arg(command, id);
+    format!(
+        "--{}",
+        arg.get_long().expect("config file options are flags")
+    )
+}
+
+fn generate_arg<'a>(command: &'a clap::Command, id: &str) -> &'a clap::Arg {
+    command
+        .get_arguments()
+        .find(|arg| arg.get_id() == id)
+        .unwrap_or_else(|| panic!("config file option '{}' has no flag", id))
+}
+
+/// `schema`, the type of option `id`'s [`ConfigFile`] field, filled out with the flag's help,
+/// default and possible values.
+fn option_schema(
+    command: &clap::Command,
+    id: &str,
+    mut schema: serde_json::Value,
+) -> serde_json::Value {
+    let arg = generate_arg(command, id);
+    let possible_values = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect::<Vec<_>>();
+    if !possible_values.is_empty() && schema["type"] != "boolean" {
+        schema = serde_json::json!({ "enum": possible_values });
+    }
+    if let [default] = arg.get_default_values() {
+        let default = default.to_string_lossy();
+        schema["default"] = match schema["type"].as_str() {
+            Some("integer" | "boolean") => serde_json::from_str(&default)
+                .unwrap_or_else(|_| serde_json::Value::String(default.to_string())),
+            _ => serde_json::Value::String(default.to_string()),
+        };
+    }
+    if let Some(help) = arg.get_help() {
+        schema["description"] = serde_json::Value::String(help.to_string());
+    }
+    schema
+}
+
+fn run(cli: Cli) -> anyhow::Result<Exit> {
+    let cancellation = CancellationToken::new();
+    let handler_token = cancellation.clone();
+    ctrlc::set_handler(move || {
+        eprintln!("Stopping after the current pack...");
+        handler_token.cancel();
+    })?;
+
+    let (base_dir, app_name) = match &cli.export_fixture {
+        Some(dir) => fixture_location(dir)?,
+        None => (cli.base_dir, cli.app_name),
+    };
+    let infra_team = match (&cli.infra_team, &cli.infra_team_name) {
+        (Some(path), _) => InfraTeam::load(path)?,
+        (None, Some(name)) => InfraTeam::named(name),
+        (None, None) => InfraTeam::default(),
+    };
     let config = Config {
-        rails_path: "/home/perryhertler/.local/share/mise/installs/ruby/3.3.5/bin/rails"
-            .to_string(),
-        base_dir: "/home/perryhertler/Software/tmp/gen-play".to_string(),
-        app_name: "my_app".to_string(),
-        num_packages: 1000,
-        codeowners_dotslash_path:
-            "https://github.com/rubyatscale/codeowners-rs/releases/download/v0.2.1/codeowners"
-                .to_string(),
-        pks_dotslash_path: "https://github.com/rubyatscale/pks/releases/download/v0.2.23/pks"
-            .to_string(),
-    };
-    build_app(config)
+        rails_path: cli.rails_path,
+        base_dir,
+        app_name,
+        num_packages: cli.num_packages,
+        codeowners_dotslash_path: cli.codeowners_dotslash_path,
+        pks_dotslash_path: cli.pks_dotslash_path,
+        cancellation,
+        metrics: Metrics::default(),
+        seed: cli.seed,
+        resume: cli.resume,
+        on_exists: cli.on_exists,
+        run_tools: cli.run_tools,
+        post_generation_hooks: cli.post_generation_hooks,
+        embedded_skeleton: cli.embedded_skeleton,
+        rails_flavor: cli.rails_flavor,
+        matrix: cli.matrix,
+        jobs: cli
+            .jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from)),
+        ruby_version: cli.ruby_version,
+        pks_dotslash_artifacts: cli.pks_dotslash_artifacts,
+        codeowners_dotslash_artifacts: cli.codeowners_dotslash_artifacts,
+        pks_version: cli.pks_version,
+        codeowners_version: cli.codeowners_version,
+        ca_bundle: cli.ca_bundle,
+        insecure_tls: cli.insecure_tls,
+        co_owned_percent: cli.co_owned_percent,
+        ownerless_percent: cli.ownerless_percent,
+        team_rename_percent: cli.team_rename_percent,
+        team_size: cli.team_size,
+        team_layout: cli.team_layout,
+        code_ownership_shape: cli.code_ownership_shape,
+        ownership_output: cli.ownership_output,
+        codeowners_files: cli.codeowners_files,
+        ownership_table: cli.ownership_table,
+        package_metadata: cli.package_metadata,
+        package_roots: cli.package_roots,
+        pack_namespaces: cli.pack_namespaces,
+        package_owner_key: cli.package_owner_key,
+        extra_owned_globs: cli.extra_owned_globs,
+        extra_unowned_globs: cli.extra_unowned_globs,
+        varied_annotations: cli.varied_annotations,
+        pathological_annotations: cli.pathological_annotations,
+        odd_files_percent: cli.odd_files_percent,
+        long_paths_percent: cli.long_paths_percent,
+        case_twins_percent: cli.case_twins_percent,
+        malformed_percent: cli.malformed_percent,
+        nested_codeowners_percent: cli.nested_codeowners_percent,
+        syntax_error_percent: cli.syntax_error_percent,
+        file_times: cli.mtime,
+        pack_docs: cli.pack_docs,
+        app_code_dirs: cli.app_code_dirs,
+        spec_support: cli.spec_support,
+        noise_files: cli.noise_files,
+        infra_team,
+        fixture_export: cli.export_fixture.is_some(),
+        preset: cli.preset,
+    };
+    let output = cli.output;
+    let mut exit = Exit::Success;
+    build_app_with_observer(config, |event| {
+        if let GenerationEvent::ToolFinished(run) = event.inner() {
+            exit = exit.after_tool_run(&run.outcome);
+        }
+        output.event(event)
+    })?;
+    Ok(exit)
+}
+
+/// Splits an --export-fixture directory into the base directory and app name it's built from.
+fn fixture_location(dir: &std::path::Path) -> anyhow::Result<(String, String)> {
+    let Some(name) = dir.file_name() else {
+        anyhow::bail!("{} doesn't name a fixture directory", dir.display());
+    };
+    let base_dir = match dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    };
+    Ok((base_dir, name.to_string_lossy().into_owned()))
+}
+
+fn bench(args: BenchArgs) -> anyhow::Result<Exit> {
+    let results = run_bench(&BenchConfig {
+        app_dir: args.app_dir,
+        runs: args.runs,
+        tool_versions: args.tool_versions.into_iter().collect(),
+        hyperfine: args.hyperfine,
+    })?;
+    let mut baselines = Baselines::load(&args.baselines)?;
+    let comparisons = if args.compare {
+        let comparisons = compare(
+            &results,
+            &baselines,
+            args.baseline_version.as_deref(),
+            args.threshold,
+        );
+        anyhow::ensure!(
+            !comparisons.is_empty(),
+            "no baseline in {} for this fixture's seed",
+            args.baselines.display()
+        );
+        comparisons
+    } else {
+        Vec::new()
+    };
+
+    for result in &results {
+        match args.output {
+            Output::Human => println!(
+                "{} {} (seed {}): median {:.1}ms, min {:.1}ms, max {:.1}ms over {} runs",
+                result.tool,
+                result.version,
+                result.seed,
+                result.median_ms,
+                result.min_ms,
+                result.max_ms,
+                result.runs
+            ),
+            Output::Json => print_json(&BenchReport::Result(result)),
+        }
+    }
+    for comparison in &comparisons {
+        match args.output {
+            Output::Human => println!(
+                "{}: {:.1}ms vs {:.1}ms for {} ({:+.1}%){}",
+                comparison.tool,
+                comparison.median_ms,
+                comparison.baseline_ms,
+                comparison.baseline_version,
+                comparison.change_percent,
+                if comparison.regressed {
+                    " REGRESSED"
+                } else {
+                    ""
+                }
+            ),
+            Output::Json => print_json(&BenchReport::Comparison(comparison)),
+        }
+    }
+
+    if args.save {
+        for result in results {
+            baselines.record(result);
+        }
+        baselines.save(&args.baselines)?;
+    }
+    Ok(
+        if comparisons.iter().any(|comparison| comparison.regressed) {
+            Exit::PerfRegression
+        } else {
+            Exit::Success
+        },
+    )
+}
+
+fn fuzz(args: FuzzArgs) -> anyhow::Result<Exit> {
+    let cancellation = CancellationToken::new();
+    let handler_token = cancellation.clone();
+    ctrlc::set_handler(move || {
+        eprintln!("Stopping after the current pack...");
+        handler_token.cancel();
+    })?;
+
+    let config = FuzzConfig {
+        template: Config {
+            base_dir: args.base_dir,
+            codeowners_dotslash_path: args.codeowners_dotslash_path,
+            pks_dotslash_path: args.pks_dotslash_path,
+            cancellation,
+            ..Config::default()
+        },
+        iterations: args.iterations,
+        seed: args.seed,
+        max_packages: args.max_packages,
+        keep_apps: args.keep_apps,
+    };
+    let mut exit = Exit::Success;
+    let mut report = |case: &FuzzCase| {
+        for finding in &case.findings {
+            exit = exit.after_finding(finding);
+        }
+        match args.output {
+            Output::Human => print_fuzz_case(case),
+            Output::Json => print_json(case),
+        }
+    };
+    match args.reproduce {
+        Some(seed) => report(&run_case(&config, seed)?),
+        None => run_fuzz(&config, report)?,
+    }
+    Ok(exit)
+}
+
+fn print_fuzz_case(case: &FuzzCase) {
+    if case.findings.is_empty() {
+        println!("seed {}: ok", case.seed);
+        return;
+    }
+    println!(
+        "seed {}: {} findings in {} (reproduce with --reproduce {})",
+        case.seed,
+        case.findings.len(),
+        case.app_dir.display(),
+        case.seed
+    );
+    for finding in &case.findings {
+        match finding {
+            Finding::ToolCrashed { command, reason } => {
+                println!("  {} crashed: {}", command, reason)
+            }
+            Finding::ToolFailed {
+                command,
+                exit_code,
+                stderr,
+            } => println!(
+                "  {} failed with exit code {}: {}",
+                command,
+                exit_code,
+                stderr.trim()
+            ),
+            Finding::OwnershipMismatch {
+                path,
+                expected,
+                actual,
+            } => println!(
+                "  {}: expected {}, got {}",
+                path.display(),
+                expected.as_deref().unwrap_or("unowned"),
+                actual.as_deref().unwrap_or("unowned")
+            ),
+        }
+    }
+}
+
+fn mutate_touch(args: TouchArgs) -> anyhow::Result<Exit> {
+    let touched = touch(&args.app_dir, args.percent, args.seed)?;
+    match args.output {
+        Output::Human => println!(
+            "Touched {} of {} files in {}",
+            touched.files.len(),
+            touched.total,
+            args.app_dir.display()
+        ),
+        Output::Json => print_json(&touched),
+    }
+    Ok(Exit::Success)
+}
+
+fn mutate_watch(args: WatchArgs) -> anyhow::Result<Exit> {
+    let cancellation = CancellationToken::new();
+    let handler_token = cancellation.clone();
+    ctrlc::set_handler(move || handler_token.cancel())?;
+
+    let changes = watch(
+        &WatchConfig {
+            app_dir: args.app_dir.clone(),
+            interval: Duration::from_millis(args.interval_ms),
+            seed: args.seed,
+            max_changes: args.max_changes,
+            cancellation,
+        },
+        |change| match args.output {
+            Output::Human => match change {
+                Change::Added { path } => println!("added {}", path.display()),
+                Change::Modified { path } => println!("modified {}", path.display()),
+                Change::Moved { from, to } => {
+                    println!("moved {} to {}", from.display(), to.display())
+                }
+            },
+            Output::Json => print_json(change),
+        },
+    )?;
+    if let Output::Human = args.output {
+        println!("Made {} changes in {}", changes, args.app_dir.display());
+    }
+    Ok(Exit::Success)
+}
+
+/// JSON lines printed by `bench`, in the same shape as generation events.
+#[derive(Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+enum BenchReport<'a> {
+    Result(&'a BenchResult),
+    Comparison(&'a Comparison),
+}
+
+/// JSON counterpart of the messages printed when generation does not finish.
+#[derive(Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+enum Failure<'a> {
+    Cancelled(&'a Cancelled),
+    Error { message: String },
+}
+
+fn print_json(value: &impl Serialize) {
+    println!(
+        "{}",
+        serde_json::to_string(value).expect("events always serialize")
+    );
+}
+
+fn print_progress(event: &GenerationEvent) {
+    if let Some(line) = progress_line(event) {
+        println!("{}", line);
+    }
+}
+
+/// What the text output shows for `event`, if anything.
+fn progress_line(event: &GenerationEvent) -> Option<String> {
+    Some(match event {
+        GenerationEvent::AppStarted { app_name, rails } => {
+            format!("== {} (rails {}) ==", app_name, rails)
+        }
+        GenerationEvent::App { app_name, event } => {
+            format!("[{}] {}", app_name, progress_line(event)?)
+        }
+        GenerationEvent::PhaseStarted(phase) => format!("{:?}...", phase),
+        GenerationEvent::PhaseFinished(_) => return None,
+        GenerationEvent::Resumed {
+            completed_packs,
+            total,
+        } => format!("Resuming after {} of {} packs", completed_packs, total),
+        GenerationEvent::PackCompleted {
+            name, index, total, ..
+        } => format!("[{}/{}] {}", index + 1, total, name),
+        GenerationEvent::PackSkipped {
+            name, index, total, ..
+        } => format!(
+            "[{}/{}] {} (skipped, team already exists)",
+            index + 1,
+            total,
+            name
+        ),
+        GenerationEvent::ToolFinished(run) => match &run.outcome {
+            ToolOutcome::Passed => format!("{}: passed in {}ms", run.command, run.duration_ms),
+            ToolOutcome::Failed { exit_code, stderr } => format!(
+                "{}: failed with exit code {}\n{}",
+                run.command, exit_code, stderr
+            ),
+            ToolOutcome::Crashed { reason } => format!("{}: crashed: {}", run.command, reason),
+        },
+        GenerationEvent::Finished {
+            files_written,
+            faults,
+        } => {
+            if faults.is_empty() {
+                format!("Done: {} files written", files_written)
+            } else {
+                format!(
+                    "Done: {} files written ({} injected faults)",
+                    files_written,
+                    faults.len()
+                )
+            }
+        }
+    })
 }
@@ -0,0 +1,66 @@
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{code_ownership::CODE_OWNERSHIP_FILE, PackOwnership, TeamLayout};
+
+/// A configuration file a pack breaks on purpose, see [`crate::Config::malformed_percent`].
+/// Tools are expected to report an error naming the file rather than crash or carry on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MalformedKind {
+    /// The pack team's YAML with an unterminated quoted scalar.
+    TeamYaml,
+    /// A `package.yml` cut off partway through a flow sequence.
+    PackageYml,
+    /// An entry in `config/code_ownership.yml` that is a list rather than a glob string.
+    CodeOwnershipEntry,
+}
+
+impl MalformedKind {
+    /// One of the kinds a pack with `ownership` can have. Unowned packs have no team YAML
+    /// to corrupt.
+    pub(crate) fn random(ownership: PackOwnership, rng: &mut impl Rng) -> Self {
+        let kinds: &[Self] = if ownership == PackOwnership::Unowned {
+            &[Self::PackageYml, Self::CodeOwnershipEntry]
+        } else {
+            &[Self::TeamYaml, Self::PackageYml, Self::CodeOwnershipEntry]
+        };
+        kinds[rng.gen_range(0..kinds.len())]
+    }
+
+    /// The broken file, relative to the app root.
+    pub(crate) fn path(self, layout: TeamLayout, pack_path: &Path, team: &str) -> PathBuf {
+        match self {
+            Self::TeamYaml => layout.team_file(Path::new(""), team),
+            Self::PackageYml => pack_path.join("package.yml"),
+            Self::CodeOwnershipEntry => PathBuf::from(CODE_OWNERSHIP_FILE),
+        }
+    }
+}
+
+/// `yaml` with the quote around the GitHub team left open, so everything after it is swallowed
+/// into one scalar that never ends.
+pub(crate) fn corrupt_team_yaml(yaml: &str) -> String {
+    yaml.replacen("'\n", "\n", 1)
+}
+
+/// A `package.yml` as it would look after an interrupted write. `owner` is kept ahead of the
+/// cut so the file still looks like it declares one.
+pub(crate) fn truncated_package_yml(pack_path: &Path, owner: Option<&str>) -> String {
+    let mut contents = owner
+        .map(|owner| format!("owner: {}\n", owner))
+        .unwrap_or_default();
+    contents.push_str(&format!(
+        "enforce_dependencies: true\nenforce_privacy: true\ndependencies: [{}_shared, packs/",
+        pack_path.display()
+    ));
+    contents
+}
+
+/// The `team_file_glob` item appended to `config/code_ownership.yml`, which is always the
+/// file's last list.
+pub(crate) fn code_ownership_entry(pack_path: &Path) -> String {
+    format!("  - [{}/**]\n", pack_path.display())
+}
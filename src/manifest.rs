@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CodeOwnershipShape, EdgeCase, EdgeCaseExpectation, MalformedKind, OddFileKind, PackOwnership,
+    SyntaxErrorKind, ViolationKind,
+};
+
+const MANIFEST_FILE: &str = "generator-manifest.json";
+const PRE_RENAME_MANIFEST_FILE: &str = "generator-manifest.pre-rename.json";
+const TEAM_ALIASES_FILE: &str = "team-aliases.yml";
+
+/// Ground truth for a generated app, written to `generator-manifest.json` in the app root so
+/// tool output can be checked against what the generator intended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub app_name: String,
+    /// The Rails binary or embedded flavor the app was built from, see [`crate::Config::rails_tag`].
+    pub rails: String,
+    pub seed: u64,
+    /// The `config/code_ownership.yml` template the app was written with. Never
+    /// [`CodeOwnershipShape::Random`].
+    #[serde(default)]
+    pub code_ownership_shape: CodeOwnershipShape,
+    /// The tool binaries downloaded into `.dotslash`.
+    #[serde(default)]
+    pub tools: Vec<ToolManifest>,
+    pub packs: Vec<PackManifest>,
+    /// Directories of code outside the packs, see [`crate::Config::app_code_dirs`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub app_code: Vec<AppCodeDir>,
+    /// Shared test infrastructure, see [`crate::Config::spec_support`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spec_support: Vec<SupportFile>,
+    /// Directories tools should skip, see [`crate::Config::noise_files`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub noise: Vec<NoiseDir>,
+}
+
+/// A directory filled with files no team owns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoiseDir {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolManifest {
+    pub name: String,
+    /// The release tag a version specifier resolved to. `None` when a pinned URL was used.
+    pub version: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    /// Relative to the app root.
+    pub path: PathBuf,
+    /// The namespace whose team owns the pack, see [`crate::Config::pack_namespaces`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// `None` for [`PackOwnership::Unowned`] packs.
+    pub team: Option<String>,
+    /// The team's name before it was renamed to `team`, see [`crate::Config::team_rename_percent`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_team: Option<String>,
+    pub ownership: PackOwnership,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub co_owner: Option<CoOwner>,
+    /// Subtrees with `.codeowner` files of their own, outermost first, see
+    /// [`crate::Config::nested_codeowners_percent`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nested_codeowners: Vec<NestedCodeowner>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotation_edge_cases: Vec<AnnotationEdgeCase>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub odd_files: Vec<OddFile>,
+    /// Ruby files that don't parse, owned like the rest of their directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub syntax_error_files: Vec<SyntaxErrorFile>,
+    /// A file whose absolute path is as long as the OS allows, relative to the app root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_path_file: Option<PathBuf>,
+    /// Files whose paths differ from another's only by case. Absent when the filesystem is
+    /// case-insensitive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub case_twins: Vec<CaseTwin>,
+    /// Ownership problems that validation should report, see [`crate::Preset::Coverage`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+    /// Markdown docs, owned by the pack's team when the configured globs cover them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub doc_files: Vec<PathBuf>,
+    /// A configuration file the pack broke on purpose, which tools should report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub malformed_file: Option<MalformedFile>,
+}
+
+/// A second team owning part of a pack, through the same mechanism as the pack's own team.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoOwner {
+    pub team: String,
+    /// Directories owned by this team rather than the pack's, relative to the app root.
+    pub directories: Vec<PathBuf>,
+}
+
+/// A directory below a pack root with a `.codeowner` file of its own. The closest one to a
+/// file decides its owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NestedCodeowner {
+    /// Relative to the app root.
+    pub directory: PathBuf,
+    pub team: String,
+}
+
+/// A file written to trip up annotation parsers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnotationEdgeCase {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub case: EdgeCase,
+    pub expectation: EdgeCaseExpectation,
+}
+
+/// A huge, binary or empty file placed among a pack's code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OddFile {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub kind: OddFileKind,
+    /// The owning team, or `None` when the file has no ownership signal.
+    pub team: Option<String>,
+}
+
+/// A Ruby file with a syntax error placed among a pack's code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyntaxErrorFile {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub kind: SyntaxErrorKind,
+    /// The owning team, or `None` in unowned packs.
+    pub team: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseTwin {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    /// The path this one differs from only by case.
+    pub twin_of: PathBuf,
+    /// The owning team, or `None` when the file has no ownership signal.
+    pub team: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MalformedFile {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub kind: MalformedKind,
+}
+
+/// A directory of code under `app`, every file in it owned by `team`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppCodeDir {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub team: String,
+    /// [`PackOwnership::FileAnnotation`] or [`PackOwnership::TeamConfig`].
+    pub ownership: PackOwnership,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupportFile {
+    /// Relative to the app root.
+    pub path: PathBuf,
+    pub team: String,
+    /// [`PackOwnership::FileAnnotation`] or [`PackOwnership::TeamConfig`].
+    pub ownership: PackOwnership,
+}
+
+impl PackManifest {
+    /// Files whose owner isn't simply that of their directory, with that owner: odd files,
+    /// case twins, annotation edge cases and violations. Case twins can be outside the pack.
+    pub(crate) fn listed_files(&self) -> Vec<(&Path, Option<String>)> {
+        let odd_files = self
+            .odd_files
+            .iter()
+            .map(|file| (file.path.as_path(), file.team.clone()));
+        let case_twins = self
+            .case_twins
+            .iter()
+            .map(|twin| (twin.path.as_path(), twin.team.clone()));
+        let edge_cases = self.annotation_edge_cases.iter().map(|edge_case| {
+            (
+                edge_case.path.as_path(),
+                self.team.clone().filter(|_| edge_case.case.owned()),
+            )
+        });
+        let violations = self
+            .violations
+            .iter()
+            .map(|violation| (violation.path.as_path(), None));
+        odd_files
+            .chain(case_twins)
+            .chain(edge_cases)
+            .chain(violations)
+            .collect()
+    }
+}
+
+impl Manifest {
+    pub fn path(app_dir: &Path) -> PathBuf {
+        app_dir.join(MANIFEST_FILE)
+    }
+
+    pub fn load(app_dir: &Path) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(
+            Self::path(app_dir),
+        )?)?)
+    }
+
+    pub(crate) fn write(&self, app_dir: &Path) -> anyhow::Result<()> {
+        std::fs::write(Self::path(app_dir), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The same app as it was before any team was renamed: every pack owned by its team's
+    /// previous name.
+    pub fn before_renames(&self) -> Self {
+        let mut manifest = self.clone();
+        for pack in &mut manifest.packs {
+            if let Some(previous) = pack.previous_team.take() {
+                pack.team = Some(previous);
+            }
+        }
+        manifest
+    }
+
+    /// Writes `team-aliases.yml`, mapping each renamed team's old name to its new one, and
+    /// the pre-rename manifest, so the app can be checked as both the old and new state.
+    pub(crate) fn write_rename_states(&self, app_dir: &Path) -> anyhow::Result<()> {
+        let mut aliases = String::from("---\naliases:\n");
+        for pack in &self.packs {
+            if let (Some(previous), Some(team)) = (&pack.previous_team, &pack.team) {
+                aliases.push_str(&format!("  {}: {}\n", previous, team));
+            }
+        }
+        std::fs::write(app_dir.join(TEAM_ALIASES_FILE), aliases)?;
+        std::fs::write(
+            app_dir.join(PRE_RENAME_MANIFEST_FILE),
+            serde_json::to_string_pretty(&self.before_renames())?,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+use faker_rand::en_us::names::{FirstName, LastName};
+use rand::Rng;
+
+/// How many members each generated team lists under `github.members`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamSize {
+    /// Every team has exactly this many members.
+    Fixed(usize),
+    /// Sizes spread evenly between `min` and `max`, inclusive.
+    Uniform { min: usize, max: usize },
+    /// Mostly teams near `min` with a few approaching `max`, like a real org chart.
+    LongTail { min: usize, max: usize },
+}
+
+impl Default for TeamSize {
+    fn default() -> Self {
+        Self::Uniform { min: 1, max: 8 }
+    }
+}
+
+impl FromStr for TeamSize {
+    type Err = anyhow::Error;
+
+    /// Parses `N`, `MIN..MAX` (uniform) or `MIN..MAX:long-tail`, with inclusive bounds.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (range, long_tail) = match s.strip_suffix(":long-tail") {
+            Some(range) => (range, true),
+            None => (s, false),
+        };
+        let Some((min, max)) = range.split_once("..") else {
+            if long_tail {
+                anyhow::bail!("a long-tail team size needs a MIN..MAX range, got '{}'", s);
+            }
+            return Ok(Self::Fixed(s.parse()?));
+        };
+        let (min, max) = (min.parse()?, max.parse()?);
+        if min > max {
+            anyhow::bail!("team size range '{}' is empty", s);
+        }
+        Ok(if long_tail {
+            Self::LongTail { min, max }
+        } else {
+            Self::Uniform { min, max }
+        })
+    }
+}
+
+impl fmt::Display for TeamSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(size) => write!(f, "{}", size),
+            Self::Uniform { min, max } => write!(f, "{}..{}", min, max),
+            Self::LongTail { min, max } => write!(f, "{}..{}:long-tail", min, max),
+        }
+    }
+}
+
+impl TeamSize {
+    fn sample(self, rng: &mut impl Rng) -> usize {
+        match self {
+            Self::Fixed(size) => size,
+            Self::Uniform { min, max } => rng.gen_range(min..=max),
+            Self::LongTail { min, max } => {
+                let skew = rng.gen::<f64>().powi(6);
+                min + ((max - min) as f64 * skew).round() as usize
+            }
+        }
+    }
+
+    /// Draws a team size and that many distinct GitHub handles.
+    pub(crate) fn members(self, rng: &mut impl Rng) -> Vec<String> {
+        let size = self.sample(rng);
+        let mut handles = BTreeSet::new();
+        while handles.len() < size {
+            handles.insert(github_handle(rng));
+        }
+        handles.into_iter().collect()
+    }
+}
+
+/// A handle in one of the shapes people actually pick: `jane-doe`, `jdoe`, `janedoe42`.
+/// Only lowercase ASCII letters, digits and single hyphens, as GitHub requires.
+fn github_handle(rng: &mut impl Rng) -> String {
+    let first = handle_part(&rng.gen::<FirstName>().to_string());
+    let last = handle_part(&rng.gen::<LastName>().to_string());
+    match rng.gen_range(0..4) {
+        0 => format!("{}-{}", first, last),
+        1 => format!("{}{}", &first[..1], last),
+        2 => format!("{}{}{}", first, last, rng.gen_range(1..100)),
+        _ => format!("{}-{}", first, rng.gen_range(1..10_000)),
+    }
+}
+
+fn handle_part(name: &str) -> String {
+    let part = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+    if part.is_empty() {
+        "dev".to_string()
+    } else {
+        part
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fixed_sizes_and_ranges() {
+        assert_eq!("4".parse::<TeamSize>().unwrap(), TeamSize::Fixed(4));
+        assert_eq!(
+            "1..8".parse::<TeamSize>().unwrap(),
+            TeamSize::Uniform { min: 1, max: 8 }
+        );
+        assert_eq!(
+            "3..3".parse::<TeamSize>().unwrap(),
+            TeamSize::Uniform { min: 3, max: 3 }
+        );
+        assert_eq!(
+            "2..40:long-tail".parse::<TeamSize>().unwrap(),
+            TeamSize::LongTail { min: 2, max: 40 }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_sizes() {
+        for size in [
+            "",
+            "many",
+            "-1",
+            "8..1",
+            "1..",
+            "..8",
+            "1..=8",
+            "1...8",
+            "4:long-tail",
+        ] {
+            assert!(size.parse::<TeamSize>().is_err(), "accepted '{}'", size);
+        }
+    }
+
+    #[test]
+    fn displays_as_it_parses() {
+        for size in ["4", "1..8", "2..40:long-tail"] {
+            assert_eq!(size.parse::<TeamSize>().unwrap().to_string(), size);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{report, Config, GenerationEvent, Phase};
+
+/// Receives counters, gauges and timings from generation, for shipping to Prometheus, StatsD
+/// and the like. Every metric is labelled with the `app` it belongs to:
+///
+/// - `files_written` (counter): files written for packs as they complete, then the rest.
+/// - `packs_completed` and `packs_skipped` (counters).
+/// - `packs_per_second` (gauge): packs completed over the duration of the packs phase.
+/// - `app_size_bytes` (gauge): the size of the finished app directory, including the Rails
+///   skeleton and any app merged into, not only what generation wrote.
+/// - `phase_duration` (timing), labelled with its `phase`.
+/// - `tool_run_duration` (timing), labelled with the tool's `command`.
+/// - `generation_duration` (timing): the whole app, including any tool runs and hooks.
+///
+/// Apps of a concurrent matrix run report from their own threads.
+pub trait MetricsSink: Send + Sync {
+    fn counter(&self, name: &str, value: u64, labels: &[(&str, &str)]);
+    fn gauge(&self, name: &str, value: f64, labels: &[(&str, &str)]);
+    fn timing(&self, name: &str, duration: Duration, labels: &[(&str, &str)]);
+}
+
+/// The [`MetricsSink`] in [`Config::metrics`], if any. Clones share the sink.
+#[derive(Clone, Default)]
+pub struct Metrics(Option<Arc<dyn MetricsSink>>);
+
+impl Metrics {
+    pub fn new(sink: impl MetricsSink + 'static) -> Self {
+        Self(Some(Arc::new(sink)))
+    }
+
+    /// Records the metrics of the app `config` describes from its events, or `None` without a
+    /// sink.
+    pub(crate) fn recorder(&self, config: &Config) -> Option<Recorder> {
+        self.0.as_ref().map(|sink| Recorder {
+            sink: sink.clone(),
+            app_name: config.app_name.clone(),
+            app_dir: config.app_dir(),
+            started: Instant::now(),
+            phases: HashMap::new(),
+            packs_completed: 0,
+            files_written: 0,
+        })
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() {
+            "Metrics(..)"
+        } else {
+            "Metrics(None)"
+        })
+    }
+}
+
+pub(crate) struct Recorder {
+    sink: Arc<dyn MetricsSink>,
+    app_name: String,
+    app_dir: PathBuf,
+    started: Instant,
+    phases: HashMap<Phase, Instant>,
+    packs_completed: usize,
+    files_written: usize,
+}
+
+impl Recorder {
+    pub(crate) fn observe(&mut self, event: &GenerationEvent) {
+        let app = [("app", self.app_name.as_str())];
+        match event {
+            GenerationEvent::PhaseStarted(phase) => {
+                self.phases.insert(*phase, Instant::now());
+            }
+            GenerationEvent::PhaseFinished(phase) => {
+                let Some(started) = self.phases.remove(phase) else {
+                    return;
+                };
+                let elapsed = started.elapsed();
+                self.sink.timing(
+                    "phase_duration",
+                    elapsed,
+                    &[app[0], ("phase", phase.name())],
+                );
+                if *phase == Phase::Packs && !elapsed.is_zero() {
+                    self.sink.gauge(
+                        "packs_per_second",
+                        self.packs_completed as f64 / elapsed.as_secs_f64(),
+                        &app,
+                    );
+                }
+            }
+            GenerationEvent::PackCompleted { files_written, .. } => {
+                self.packs_completed += 1;
+                self.files_written += files_written;
+                self.sink.counter("packs_completed", 1, &app);
+                self.sink
+                    .counter("files_written", *files_written as u64, &app);
+            }
+            GenerationEvent::PackSkipped { .. } => self.sink.counter("packs_skipped", 1, &app),
+            GenerationEvent::ToolFinished(run) => self.sink.timing(
+                "tool_run_duration",
+                Duration::from_millis(run.duration_ms as u64),
+                &[app[0], ("command", run.command.as_str())],
+            ),
+            GenerationEvent::Finished { files_written, .. } => {
+                self.sink.counter(
+                    "files_written",
+                    files_written.saturating_sub(self.files_written) as u64,
+                    &app,
+                );
+                if let Ok((_, bytes)) = report::disk_usage(&self.app_dir) {
+                    self.sink.gauge("app_size_bytes", bytes as f64, &app);
+                }
+                self.sink
+                    .timing("generation_duration", self.started.elapsed(), &app);
+            }
+            GenerationEvent::AppStarted { .. }
+            | GenerationEvent::App { .. }
+            | GenerationEvent::Resumed { .. } => {}
+        }
+    }
+}
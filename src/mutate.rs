@@ -0,0 +1,187 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use convert_case::{Case, Casing};
+use rand::{rngs::StdRng, seq::index, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::{noise, random_name, timestamps, CancellationToken};
+
+/// Longest [`watch`] sleeps before checking its cancellation token again.
+const CANCELLATION_POLL: Duration = Duration::from_millis(100);
+
+/// Directories left alone by mutations: the tool binaries and the caches tools write.
+const SKIPPED_DIRS: &[&str] = &[".dotslash", "tmp"];
+
+/// What [`touch`] did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Touched {
+    /// Relative to the app root, sorted.
+    pub files: Vec<PathBuf>,
+    /// Files that were candidates for touching.
+    pub total: usize,
+}
+
+/// Sets the mtime of `percent` of the app's files to now without changing their contents, so
+/// mtime-based caches see them as modified. The files are picked with `seed`, or at random when
+/// it is unset.
+pub fn touch(app_dir: &Path, percent: u8, seed: Option<u64>) -> anyhow::Result<Touched> {
+    anyhow::ensure!(percent <= 100, "can't touch more than 100% of the files");
+    let candidates = app_files(app_dir)?;
+    let count = candidates.len() * usize::from(percent) / 100;
+    let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(rand::random));
+    let mut files = index::sample(&mut rng, candidates.len(), count)
+        .into_iter()
+        .map(|index| candidates[index].clone())
+        .collect::<Vec<_>>();
+    files.sort();
+    let now = SystemTime::now();
+    for file in &files {
+        timestamps::set_mtime(&app_dir.join(file), now)?;
+    }
+    Ok(Touched {
+        files,
+        total: candidates.len(),
+    })
+}
+
+/// Options for [`watch`].
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    /// A previously generated app.
+    pub app_dir: PathBuf,
+    /// Time between changes.
+    pub interval: Duration,
+    /// Seeds the changes. A random seed is used when unset.
+    pub seed: Option<u64>,
+    /// Stop after this many changes instead of running until cancelled.
+    pub max_changes: Option<usize>,
+    /// Stops watching, checked while waiting for the next change.
+    pub cancellation: CancellationToken,
+}
+
+/// One change [`watch`] made, with paths relative to the app root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    /// A new, unannotated class beside an existing Ruby file.
+    Added { path: PathBuf },
+    /// A comment appended to a Ruby file.
+    Modified { path: PathBuf },
+    /// A Ruby file moved to the directory of another, often into another pack and so to
+    /// another owner.
+    Moved { from: PathBuf, to: PathBuf },
+}
+
+/// Keeps changing the app's Ruby files outside its noise directories, one random addition,
+/// edit or move every `config.interval`, the way a developer working in it would, for
+/// soak-testing tools that watch the app. `on_change` is called after each. Runs until
+/// `config.max_changes` or until cancelled, and returns how many changes were made. The
+/// manifest isn't updated, so moved and added files may no longer have the owners it
+/// describes.
+pub fn watch(config: &WatchConfig, mut on_change: impl FnMut(&Change)) -> anyhow::Result<usize> {
+    let mut files = app_files(&config.app_dir)?
+        .into_iter()
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "rb")
+                && !noise::NOISE_DIRS
+                    .iter()
+                    .any(|(dir, _)| path.starts_with(dir))
+        })
+        .collect::<Vec<_>>();
+    anyhow::ensure!(
+        !files.is_empty(),
+        "{} has no Ruby files to change",
+        config.app_dir.display()
+    );
+    let mut rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+    let mut changes = 0;
+    while config.max_changes.is_none_or(|max| changes < max) {
+        let deadline = Instant::now() + config.interval;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if config.cancellation.is_cancelled() {
+                return Ok(changes);
+            }
+            std::thread::sleep(remaining.min(CANCELLATION_POLL));
+        }
+        if config.cancellation.is_cancelled() {
+            return Ok(changes);
+        }
+        let change = change_file(&config.app_dir, &mut files, &mut rng, changes)?;
+        changes += 1;
+        on_change(&change);
+    }
+    Ok(changes)
+}
+
+/// Makes one random change to `files`, keeping the list up to date. `number` tells edits
+/// apart.
+fn change_file(
+    app_dir: &Path,
+    files: &mut Vec<PathBuf>,
+    rng: &mut StdRng,
+    number: usize,
+) -> anyhow::Result<Change> {
+    let index = rng.gen_range(0..files.len());
+    Ok(match rng.gen_range(0..3) {
+        0 => {
+            let dir = files[index].parent().unwrap_or(Path::new("")).to_path_buf();
+            let (path, name) = free_file_name(app_dir, &dir, rng);
+            std::fs::write(
+                app_dir.join(&path),
+                format!("class {}\nend\n", name.to_case(Case::Pascal)),
+            )?;
+            files.push(path.clone());
+            Change::Added { path }
+        }
+        1 => {
+            let path = files[index].clone();
+            let mut contents = std::fs::read(app_dir.join(&path))?;
+            contents.extend_from_slice(format!("\n# Edit {}\n", number + 1).as_bytes());
+            std::fs::write(app_dir.join(&path), contents)?;
+            Change::Modified { path }
+        }
+        _ => {
+            let from = files[index].clone();
+            let dir = files[rng.gen_range(0..files.len())]
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+            let to = match from.file_name() {
+                Some(name) if !app_dir.join(&dir).join(name).exists() => dir.join(name),
+                _ => free_file_name(app_dir, &dir, rng).0,
+            };
+            std::fs::rename(app_dir.join(&from), app_dir.join(&to))?;
+            files[index] = to.clone();
+            Change::Moved { from, to }
+        }
+    })
+}
+
+/// A Ruby file in `dir` that doesn't exist yet, and the name it was made from.
+fn free_file_name(app_dir: &Path, dir: &Path, rng: &mut StdRng) -> (PathBuf, String) {
+    loop {
+        let name = random_name(rng);
+        let path = dir.join(format!("{}.rb", name));
+        if !app_dir.join(&path).exists() {
+            return (path, name);
+        }
+    }
+}
+
+/// The app's files relative to its root, leaving out [`SKIPPED_DIRS`].
+pub(crate) fn app_files(app_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    Ok(timestamps::walk(app_dir, false)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(app_dir).ok()?.to_path_buf();
+            let skipped = relative
+                .components()
+                .next()
+                .is_some_and(|first| SKIPPED_DIRS.iter().any(|dir| first.as_os_str() == *dir));
+            (!skipped).then_some(relative)
+        })
+        .collect())
+}
@@ -0,0 +1,78 @@
+use std::{fmt, str::FromStr};
+
+use rand::Rng;
+
+/// Domains packs are grouped under with [`PackNamespaces::Prefixed`] or
+/// [`PackNamespaces::Nested`].
+const NAMESPACES: &[&str] = &["payments", "billing", "identity", "shipping"];
+
+/// How packs are grouped into namespaces. Every pack in a namespace belongs to the namespace's
+/// team, owned the same way, so a team owning a whole domain shows up as packs sharing a
+/// prefix.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PackNamespaces {
+    /// Every pack stands alone, directly under its package root.
+    #[default]
+    Flat,
+    /// `packs/payments_refunds`, with team YAML owning `packs/payments_*/**`.
+    Prefixed,
+    /// `packs/payments/refunds`, with team YAML owning `packs/payments/**`.
+    Nested,
+}
+
+impl FromStr for PackNamespaces {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Self::Flat),
+            "prefixed" => Ok(Self::Prefixed),
+            "nested" => Ok(Self::Nested),
+            _ => anyhow::bail!(
+                "unknown pack namespaces '{}' (expected flat, prefixed or nested)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PackNamespaces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Flat => "flat",
+            Self::Prefixed => "prefixed",
+            Self::Nested => "nested",
+        })
+    }
+}
+
+impl PackNamespaces {
+    /// A namespace and its index for the next pack, or `None` for [`Self::Flat`].
+    pub(crate) fn pick(self, rng: &mut impl Rng) -> Option<(usize, &'static str)> {
+        (self != Self::Flat).then(|| {
+            let index = rng.gen_range(0..NAMESPACES.len());
+            (index, NAMESPACES[index])
+        })
+    }
+
+    /// The name of pack `name` in `namespace`, prefixed for [`Self::Prefixed`].
+    pub(crate) fn pack_name(self, namespace: &str, name: &str) -> String {
+        match self {
+            Self::Prefixed => format!("{}_{}", namespace, name),
+            Self::Flat | Self::Nested => name.to_string(),
+        }
+    }
+
+    /// The `owned_globs` entry covering every pack of `namespace` under `root`.
+    pub(crate) fn owned_glob(self, root: &str, namespace: &str) -> String {
+        match self {
+            Self::Flat | Self::Prefixed => format!("{}/{}_*/**", root, namespace),
+            Self::Nested => format!("{}/{}/**", root, namespace),
+        }
+    }
+}
+
+/// The team owning every pack in `namespace`.
+pub(crate) fn team_name(namespace: &str) -> String {
+    format!("{}-team", namespace)
+}
@@ -0,0 +1,23 @@
+use std::path::{Path, PathBuf};
+
+/// Directories below a pack root that get `.codeowner` files of their own, outermost first,
+/// in `app/services/b` since that is never co-owned. The first hands its subtree to another
+/// team and the second, inside it, hands its subtree back to the pack's team, so only a tool
+/// that picks the closest `.codeowner` gets both right.
+const NESTED_DIRS: [&str; 2] = ["app/services/b/nested", "app/services/b/nested/deeper"];
+
+/// Code files written to each nested directory.
+pub(crate) const FILES_PER_NESTED_DIR: usize = 3;
+
+/// The team a pack hands its nested subtree to.
+pub(crate) fn team_name(pack: &str) -> String {
+    format!("{}-nested-team", pack)
+}
+
+/// The nested directories relative to `pack_path`, each with the team its `.codeowner` names.
+pub(crate) fn directories(pack_path: &Path, pack: &str, pack_team: &str) -> Vec<(PathBuf, String)> {
+    vec![
+        (pack_path.join(NESTED_DIRS[0]), team_name(pack)),
+        (pack_path.join(NESTED_DIRS[1]), pack_team.to_string()),
+    ]
+}
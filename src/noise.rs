@@ -0,0 +1,160 @@
+use std::path::{Path, PathBuf};
+
+use convert_case::{Case, Casing};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{manifest::NoiseDir, random_name, Config};
+
+/// Salt for the RNG naming noise packages and gems.
+const NOISE_SEED_SALT: u64 = 0x6E6F_6973_6566_696C;
+
+/// Where noise goes, with each directory's share of [`Config::noise_files`] in percent.
+pub(crate) const NOISE_DIRS: &[(&str, usize)] = &[
+    ("node_modules", 60),
+    ("vendor/bundle", 25),
+    ("tmp/cache", 10),
+    ("log", 5),
+];
+
+/// Files written to each package in `node_modules` and each gem in `vendor/bundle`.
+const FILES_PER_PACKAGE: usize = 20;
+
+/// Fills the directories tools should skip with [`Config::noise_files`] files between them:
+/// npm packages, installed gems full of Ruby, bootsnap caches and logs. Returns each directory
+/// with the number of files written to it.
+pub(crate) fn write(config: &Config, seed: u64) -> anyhow::Result<Vec<NoiseDir>> {
+    let app_dir = config.app_dir();
+    let mut rng = StdRng::seed_from_u64(seed ^ NOISE_SEED_SALT);
+    let mut dirs = Vec::new();
+    let mut remaining = config.noise_files;
+    for (index, (dir, share)) in NOISE_DIRS.iter().enumerate() {
+        let files = if index == NOISE_DIRS.len() - 1 {
+            remaining
+        } else {
+            (config.noise_files * share / 100).min(remaining)
+        };
+        remaining -= files;
+        if files == 0 {
+            continue;
+        }
+        let path = PathBuf::from(dir);
+        let root = app_dir.join(&path);
+        match *dir {
+            "node_modules" => write_packages(&root, false, files, &mut rng, node_module_file)?,
+            "vendor/bundle" => write_packages(
+                &root.join("ruby/3.3.0/gems"),
+                true,
+                files,
+                &mut rng,
+                installed_gem_file,
+            )?,
+            "tmp/cache" => write_cache(&root, files, &mut rng)?,
+            _ => write_logs(&root, files)?,
+        }
+        dirs.push(NoiseDir { path, files });
+    }
+    Ok(dirs)
+}
+
+/// Writes `files` files to packages of [`FILES_PER_PACKAGE`] under `root`, with `file` giving
+/// the path and contents of each file of a package. `versioned` packages have the version in
+/// their directory name, the way installed gems do.
+fn write_packages(
+    root: &Path,
+    versioned: bool,
+    files: usize,
+    rng: &mut StdRng,
+    file: fn(&str, &str, usize) -> (PathBuf, String),
+) -> anyhow::Result<()> {
+    for package in 0..files.div_ceil(FILES_PER_PACKAGE) {
+        let name = format!("{}-{}", random_name(rng).replace('_', "-"), package);
+        let version = format!(
+            "{}.{}.{}",
+            rng.gen_range(0..5),
+            rng.gen_range(0..20),
+            rng.gen_range(0..10)
+        );
+        let package_dir = if versioned {
+            root.join(format!("{}-{}", name, version))
+        } else {
+            root.join(&name)
+        };
+        let count = FILES_PER_PACKAGE.min(files - package * FILES_PER_PACKAGE);
+        for index in 0..count {
+            let (path, contents) = file(&name, &version, index);
+            let path = package_dir.join(path);
+            std::fs::create_dir_all(path.parent().expect("package files are in the package"))?;
+            std::fs::write(path, contents)?;
+        }
+    }
+    Ok(())
+}
+
+fn node_module_file(name: &str, version: &str, index: usize) -> (PathBuf, String) {
+    match index {
+        0 => (
+            PathBuf::from("package.json"),
+            format!(
+                "{{\n  \"name\": \"{}\",\n  \"version\": \"{}\",\n  \"main\": \"index.js\"\n}}\n",
+                name, version
+            ),
+        ),
+        1 => (
+            PathBuf::from("index.js"),
+            "module.exports = require('./lib/0');\n".to_string(),
+        ),
+        _ => (
+            PathBuf::from(format!("lib/{}.js", index - 2)),
+            format!("exports.value = {};\n", index),
+        ),
+    }
+}
+
+fn installed_gem_file(name: &str, version: &str, index: usize) -> (PathBuf, String) {
+    let file_name = name.replace('-', "_");
+    let module = file_name.to_case(Case::Pascal);
+    match index {
+        0 => (
+            PathBuf::from(format!("{}.gemspec", file_name)),
+            format!(
+                "Gem::Specification.new do |s|\n  s.name = '{}'\n  s.version = '{}'\nend\n",
+                name, version
+            ),
+        ),
+        1 => (
+            PathBuf::from(format!("lib/{}.rb", file_name)),
+            format!("module {}\nend\n", module),
+        ),
+        _ => (
+            PathBuf::from(format!("lib/{}/file_{}.rb", file_name, index - 2)),
+            format!("module {}\n  class File{}\n  end\nend\n", module, index - 2),
+        ),
+    }
+}
+
+/// Bootsnap's compile cache: binary blobs in directories named after two hex digits.
+fn write_cache(root: &Path, files: usize, rng: &mut StdRng) -> anyhow::Result<()> {
+    let cache_dir = root.join("bootsnap/compile-cache-iseq");
+    for index in 0..files {
+        let dir = cache_dir.join(format!("{:02x}", rng.gen::<u8>()));
+        std::fs::create_dir_all(&dir)?;
+        let blob = (0..64).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        // The index keeps names unique.
+        std::fs::write(
+            dir.join(format!("{:08x}{:06x}", rng.gen::<u32>(), index)),
+            blob,
+        )?;
+    }
+    Ok(())
+}
+
+fn write_logs(root: &Path, files: usize) -> anyhow::Result<()> {
+    std::fs::create_dir_all(root)?;
+    for index in 0..files {
+        std::fs::write(
+            root.join(format!("development.log.{}", index)),
+            "Started GET \"/\" for 127.0.0.1\nCompleted 200 OK in 5ms\n",
+        )?;
+    }
+    Ok(())
+}
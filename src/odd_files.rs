@@ -0,0 +1,61 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Minimum size of [`OddFileKind::Huge`] files.
+const HUGE_FILE_BYTES: usize = 4 * 1024 * 1024;
+/// Size of [`OddFileKind::Binary`] files.
+const BINARY_FILE_BYTES: usize = 64 * 1024;
+
+/// A file ownership tools have to skip or survive rather than parse like ordinary source,
+/// see [`crate::Config::odd_files_percent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OddFileKind {
+    /// A multi-megabyte Ruby file.
+    Huge,
+    /// Random bytes, including NULs and invalid UTF-8, behind an `.rb` extension.
+    Binary,
+    /// A zero-byte file.
+    Empty,
+}
+
+impl OddFileKind {
+    pub const ALL: [Self; 3] = [Self::Huge, Self::Binary, Self::Empty];
+
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            Self::Huge => "huge_generated_file.rb",
+            Self::Binary => "binary_blob.rb",
+            Self::Empty => "empty_file.rb",
+        }
+    }
+
+    /// Whether the file carries the pack's `# @team` annotation in annotation-owned packs.
+    /// The binary and empty files have nowhere to put one.
+    pub(crate) fn annotated(self) -> bool {
+        self == Self::Huge
+    }
+
+    /// The file's contents. `annotation` is the `# @team` line for annotated files.
+    pub(crate) fn contents(self, annotation: Option<&str>, rng: &mut impl Rng) -> Vec<u8> {
+        match self {
+            Self::Huge => {
+                let mut contents = annotation.unwrap_or_default().to_string();
+                contents.push_str("class HugeGeneratedFile\n");
+                let mut method = 0;
+                while contents.len() < HUGE_FILE_BYTES {
+                    contents.push_str(&format!("  def method_{}; {}; end\n", method, method));
+                    method += 1;
+                }
+                contents.push_str("end\n");
+                contents.into_bytes()
+            }
+            Self::Binary => {
+                let mut bytes = vec![0u8; BINARY_FILE_BYTES];
+                rng.fill(&mut bytes[..]);
+                bytes
+            }
+            Self::Empty => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use crate::{Config, ToolManifest};
+
+/// Stands in for the download cache when the crate is built without the `download` feature.
+/// It can't be created, so the code that would use it never runs.
+pub(crate) enum Downloads {}
+
+impl Downloads {
+    pub fn new(_config: &Config) -> anyhow::Result<Self> {
+        anyhow::bail!("built without the `download` feature, so the tools can't be downloaded")
+    }
+
+    pub fn fetch(&self, _url: &str) -> anyhow::Result<Arc<Vec<u8>>> {
+        match *self {}
+    }
+
+    pub fn resolve_tools(&self, _config: &Config) -> anyhow::Result<(ToolManifest, ToolManifest)> {
+        match *self {}
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{mutate, Config, Manifest, PackManifest, PackOwnership};
+
+const TABLE_FILE: &str = "generator-ownership";
+
+/// File format of the ownership table, see [`Config::ownership_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Csv,
+    /// Needs the `parquet` feature.
+    Parquet,
+}
+
+impl FromStr for TableFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => anyhow::bail!("unknown table format '{}' (expected csv or parquet)", s),
+        }
+    }
+}
+
+impl fmt::Display for TableFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        })
+    }
+}
+
+/// One file of the app and the owner the generator intended for it.
+#[derive(Debug, Clone)]
+pub(crate) struct Row {
+    /// Relative to the app root.
+    pub(crate) path: PathBuf,
+    pub(crate) team: Option<String>,
+    pub(crate) ownership: PackOwnership,
+}
+
+/// A row for every file in the app, with the owner the generator intended, whether or not the
+/// configured `owned_globs` make tools look at the file.
+pub(crate) fn rows(
+    app_dir: &Path,
+    config: &Config,
+    manifest: &Manifest,
+) -> anyhow::Result<Vec<Row>> {
+    let owners = Owners::new(config, manifest);
+    Ok(mutate::app_files(app_dir)?
+        .into_iter()
+        .map(|path| {
+            let (team, ownership) = owners.owner(&path);
+            Row {
+                path,
+                team,
+                ownership,
+            }
+        })
+        .collect())
+}
+
+/// Writes `generator-ownership.csv` or `.parquet` to the app root: a `path,team,strategy` row
+/// for each of `rows`, the team empty for files nobody owns.
+pub(crate) fn write(app_dir: &Path, rows: &[Row], format: TableFormat) -> anyhow::Result<()> {
+    match format {
+        TableFormat::Csv => write_csv(&app_dir.join(TABLE_FILE).with_extension("csv"), rows),
+        TableFormat::Parquet => {
+            write_parquet(&app_dir.join(TABLE_FILE).with_extension("parquet"), rows)
+        }
+    }
+}
+
+/// The manifest indexed by path, to look up the owner of any file in the app.
+pub(crate) struct Owners<'a> {
+    config: &'a Config,
+    manifest: &'a Manifest,
+    infra_globs: Vec<String>,
+    packs: HashMap<&'a Path, &'a PackManifest>,
+    /// Files a pack lists with an owner of their own, see [`PackManifest::listed_files`].
+    listed: HashMap<&'a Path, (Option<String>, PackOwnership)>,
+}
+
+impl<'a> Owners<'a> {
+    pub(crate) fn new(config: &'a Config, manifest: &'a Manifest) -> Self {
+        let mut listed = HashMap::new();
+        for pack in &manifest.packs {
+            for (path, team) in pack.listed_files() {
+                let ownership = if team.is_some() {
+                    pack.ownership
+                } else {
+                    PackOwnership::Unowned
+                };
+                listed.entry(path).or_insert((team, ownership));
+            }
+        }
+        Self {
+            config,
+            manifest,
+            infra_globs: config.infra_team.owned_globs(config),
+            packs: manifest
+                .packs
+                .iter()
+                .map(|pack| (pack.path.as_path(), pack))
+                .collect(),
+            listed,
+        }
+    }
+
+    /// The team and mechanism owning `path`, the most specific declaration winning.
+    pub(crate) fn owner(&self, path: &Path) -> (Option<String>, PackOwnership) {
+        // Listed files can be outside their pack, like case twins in the twin pack.
+        if let Some(owner) = self.listed.get(path) {
+            return owner.clone();
+        }
+        if let Some(pack) = path.ancestors().find_map(|dir| self.packs.get(dir)) {
+            // Outermost first, so the last match is the closest.
+            if let Some(nested) = pack
+                .nested_codeowners
+                .iter()
+                .rev()
+                .find(|nested| path.starts_with(&nested.directory))
+            {
+                return (Some(nested.team.clone()), pack.ownership);
+            }
+            if let Some(co_owner) = pack.co_owner.as_ref().filter(|co_owner| {
+                co_owner
+                    .directories
+                    .iter()
+                    .any(|directory| path.starts_with(directory))
+            }) {
+                return (Some(co_owner.team.clone()), pack.ownership);
+            }
+            return (pack.team.clone(), pack.ownership);
+        }
+        if let Some(dir) = self
+            .manifest
+            .app_code
+            .iter()
+            .find(|dir| path.starts_with(&dir.path))
+        {
+            return (Some(dir.team.clone()), dir.ownership);
+        }
+        if let Some(file) = self
+            .manifest
+            .spec_support
+            .iter()
+            .find(|file| file.path == path)
+        {
+            return (Some(file.team.clone()), file.ownership);
+        }
+        let path = path.to_string_lossy();
+        if self
+            .infra_globs
+            .iter()
+            .any(|glob| glob_matches(glob, &path))
+        {
+            return (
+                Some(self.config.infra_team.name.clone()),
+                PackOwnership::TeamConfig,
+            );
+        }
+        (None, PackOwnership::Unowned)
+    }
+}
+
+/// Matches `path` against an `owned_globs` entry, where `*` stays within a directory and `**`
+/// spans any number of them.
+pub(crate) fn glob_matches(glob: &str, path: &str) -> bool {
+    if let Some(rest) = glob.strip_prefix("**") {
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        return rest.is_empty()
+            || (0..=path.len())
+                .filter(|&index| index == 0 || path[..index].ends_with('/'))
+                .any(|index| glob_matches(rest, &path[index..]));
+    }
+    match glob.chars().next() {
+        None => path.is_empty(),
+        Some('*') => path
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(path.len()))
+            .take_while(|&index| !path[..index].contains('/'))
+            .any(|index| glob_matches(&glob[1..], &path[index..])),
+        Some(c) => path
+            .strip_prefix(c)
+            .is_some_and(|path| glob_matches(&glob[c.len_utf8()..], path)),
+    }
+}
+
+fn write_csv(path: &Path, rows: &[Row]) -> anyhow::Result<()> {
+    let mut csv = String::from("path,team,strategy\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(&row.path.to_string_lossy()),
+            csv_field(row.team.as_deref().unwrap_or_default()),
+            row.ownership.name()
+        ));
+    }
+    std::fs::write(path, csv)?;
+    Ok(())
+}
+
+/// `field` quoted when it holds a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &Path, rows: &[Row]) -> anyhow::Result<()> {
+    use std::sync::Arc;
+
+    use parquet::{
+        data_type::{ByteArray, ByteArrayType},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+
+    let schema = parse_message_type(
+        "message ownership {
+            REQUIRED BYTE_ARRAY path (UTF8);
+            OPTIONAL BYTE_ARRAY team (UTF8);
+            REQUIRED BYTE_ARRAY strategy (UTF8);
+        }",
+    )?;
+    let paths = rows
+        .iter()
+        .map(|row| ByteArray::from(row.path.to_string_lossy().as_ref()))
+        .collect::<Vec<_>>();
+    let teams = rows
+        .iter()
+        .filter_map(|row| row.team.as_deref().map(ByteArray::from))
+        .collect::<Vec<_>>();
+    let team_levels = rows
+        .iter()
+        .map(|row| i16::from(row.team.is_some()))
+        .collect::<Vec<_>>();
+    let strategies = rows
+        .iter()
+        .map(|row| ByteArray::from(row.ownership.name()))
+        .collect::<Vec<_>>();
+    let columns = [
+        (paths, None),
+        (teams, Some(team_levels.as_slice())),
+        (strategies, None),
+    ];
+
+    let mut writer = SerializedFileWriter::new(
+        std::fs::File::create(path)?,
+        Arc::new(schema),
+        Arc::new(WriterProperties::builder().build()),
+    )?;
+    let mut row_group = writer.next_row_group()?;
+    for (values, def_levels) in columns {
+        let mut column = row_group
+            .next_column()?
+            .expect("the schema has a column for each");
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&values, def_levels, None)?;
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_path: &Path, _rows: &[Row]) -> anyhow::Result<()> {
+    anyhow::bail!("built without the `parquet` feature, so the ownership table can only be CSV")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_whole_paths() {
+        assert!(glob_matches(
+            "config/code_ownership.yml",
+            "config/code_ownership.yml"
+        ));
+        assert!(!glob_matches("lib/**", "mylib/tasks/x.rake"));
+        assert!(!glob_matches("packs/**", "app/packs/x.rb"));
+        assert!(!glob_matches("packs/foo/*.rb", "packs/foobar/x.rb"));
+        assert!(!glob_matches("app/*.rb", "app/x.rb.orig"));
+    }
+
+    #[test]
+    fn single_stars_stay_within_a_directory() {
+        assert!(glob_matches("*.rb", "x.rb"));
+        assert!(!glob_matches("*.rb", "app/x.rb"));
+        assert!(glob_matches("packs/*/app/*.rb", "packs/foo/app/x.rb"));
+        assert!(!glob_matches("packs/*/app/*.rb", "packs/foo/bar/app/x.rb"));
+    }
+
+    #[test]
+    fn double_stars_span_any_number_of_directories() {
+        assert!(glob_matches("**/*.rb", "x.rb"));
+        assert!(glob_matches("**/*.rb", "app/models/x.rb"));
+        assert!(glob_matches("app/**/*.rb", "app/x.rb"));
+        assert!(glob_matches("app/**/*.rb", "app/models/concerns/x.rb"));
+        assert!(glob_matches("**/vendor/**/*", "packs/foo/vendor/gems/x.rb"));
+        assert!(!glob_matches("**/vendor/**/*", "packs/foo/vendored/x.rb"));
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("packs/foo/app/x.rb"), "packs/foo/app/x.rb");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b.rb"), "\"a,b.rb\"");
+        assert_eq!(csv_field("say \"hi\".rb"), "\"say \"\"hi\"\".rb\"");
+        assert_eq!(csv_field("line\nbreak.rb"), "\"line\nbreak.rb\"");
+        assert_eq!(csv_field("carriage\rreturn.rb"), "\"carriage\rreturn.rb\"");
+    }
+}
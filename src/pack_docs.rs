@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+
+/// Markdown written to every pack with [`crate::Config::pack_docs`], relative to the pack root.
+const DOC_FILES: &[&str] = &[
+    "README.md",
+    "docs/index.md",
+    "docs/adr/0001-record-architecture-decisions.md",
+];
+
+/// The pack's doc files relative to the app root, given the pack's relative path.
+pub(crate) fn doc_files(pack_path: &Path) -> Vec<PathBuf> {
+    DOC_FILES.iter().map(|file| pack_path.join(file)).collect()
+}
+
+/// Writes the pack's docs under `pack_dir`, `pack_path` being where that is in the app.
+/// `annotation` is the HTML comment naming the team, for packs whose files are owned through
+/// annotations.
+pub(crate) fn write(
+    pack_dir: &Path,
+    pack: &str,
+    pack_path: &Path,
+    annotation: Option<&str>,
+) -> anyhow::Result<usize> {
+    for file in DOC_FILES {
+        let path = pack_dir.join(file);
+        std::fs::create_dir_all(path.parent().expect("doc files are below the pack"))?;
+        let mut contents = annotation.unwrap_or_default().to_string();
+        contents.push_str(&body(file, pack, pack_path));
+        std::fs::write(path, contents)?;
+    }
+    Ok(DOC_FILES.len())
+}
+
+fn body(file: &str, pack: &str, pack_path: &Path) -> String {
+    match file {
+        "README.md" => format!(
+            "# {}\n\nServices for {} live in `app/services`. See [the docs](docs/index.md) for how \
+             the pack fits together.\n\n## Development\n\nRun `bin/rspec {}` before \
+             pushing.\n",
+            pack,
+            pack,
+            pack_path.display()
+        ),
+        "docs/index.md" => format!(
+            "# {} documentation\n\n- [Architecture decisions](adr/)\n",
+            pack
+        ),
+        _ => "# 1. Record architecture decisions\n\n## Status\n\nAccepted\n\n## Context\n\nWe \
+              need to record the architectural decisions made in this pack.\n\n## Decision\n\nWe \
+              will use Architecture Decision Records.\n"
+            .to_string(),
+    }
+}
@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use rand::Rng;
+
+/// A directory packs are generated under. Plenty of apps never adopted the `packs/` convention,
+/// so tools must find packs wherever packwerk's `package_paths` point.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PackageRoot {
+    #[default]
+    Packs,
+    Components,
+    /// Shares its name with the default `vendored_gems_path` of `config/code_ownership.yml`,
+    /// which moves to `vendor/gems` when this root is used.
+    Gems,
+    Domains,
+}
+
+impl FromStr for PackageRoot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "packs" => Ok(Self::Packs),
+            "components" => Ok(Self::Components),
+            "gems" => Ok(Self::Gems),
+            "domains" => Ok(Self::Domains),
+            _ => anyhow::bail!(
+                "unknown package root '{}' (expected packs, components, gems or domains)",
+                s
+            ),
+        }
+    }
+}
+
+impl PackageRoot {
+    pub fn dir(self) -> &'static str {
+        match self {
+            Self::Packs => "packs",
+            Self::Components => "components",
+            Self::Gems => "gems",
+            Self::Domains => "domains",
+        }
+    }
+
+    /// One of `roots`, or [`Self::Packs`] when there are none.
+    pub(crate) fn pick(roots: &[Self], rng: &mut impl Rng) -> Self {
+        match roots {
+            [] => Self::Packs,
+            [root] => *root,
+            _ => roots[rng.gen_range(0..roots.len())],
+        }
+    }
+}
+
+/// `packwerk.yml` pointing `package_paths` at each of `roots`, and at the namespaces inside
+/// them when packs are `nested`.
+pub(crate) fn packwerk_yml(roots: &[PackageRoot], nested: bool) -> String {
+    let mut contents = String::from("package_paths:\n");
+    for (index, root) in roots.iter().enumerate() {
+        if !roots[..index].contains(root) {
+            contents.push_str(&format!("  - \"{}/*\"\n", root.dir()));
+            if nested {
+                contents.push_str(&format!("  - \"{}/*/*\"\n", root.dir()));
+            }
+        }
+    }
+    contents
+}
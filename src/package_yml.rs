@@ -0,0 +1,158 @@
+use std::{fmt, str::FromStr};
+
+use rand::Rng;
+
+/// Product groups a pack can be filed under with [`PackageMetadata::ProductGroup`].
+const PRODUCT_GROUPS: &[&str] = &[
+    "Payments",
+    "Growth",
+    "Platform",
+    "Customer Experience",
+    "Data",
+];
+
+/// The checks `package_protections` enforces, each set to its strictest rollout mode.
+const PROTECTIONS: &[&str] = &[
+    "prevent_this_package_from_violating_its_stated_dependencies",
+    "prevent_other_packages_from_using_this_packages_internals",
+    "prevent_this_package_from_exposing_an_untyped_api",
+    "prevent_this_package_from_creating_other_namespaces",
+];
+
+/// A key under `metadata:` in generated `package.yml` files. Real packs carry all sorts of
+/// metadata next to their owner, which tools reading the owner must step over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageMetadata {
+    /// `protections:`, as written by the `package_protections` gem.
+    Protections,
+    /// Keys no tool knows about: a Slack channel, a docs URL and a list of stewards.
+    CustomKeys,
+    /// `product_group:`, naming one of a few made-up product areas.
+    ProductGroup,
+}
+
+impl FromStr for PackageMetadata {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "protections" => Ok(Self::Protections),
+            "custom-keys" => Ok(Self::CustomKeys),
+            "product-group" => Ok(Self::ProductGroup),
+            _ => anyhow::bail!(
+                "unknown package.yml metadata '{}' (expected protections, custom-keys or product-group)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PackageMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Protections => "protections",
+            Self::CustomKeys => "custom-keys",
+            Self::ProductGroup => "product-group",
+        })
+    }
+}
+
+/// Where `package.yml` declares the pack's owner. Both conventions exist in the wild, and tools
+/// must resolve them to the same team.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PackageOwnerKey {
+    /// A top-level `owner:` key.
+    #[default]
+    TopLevel,
+    /// `owner:` nested under `metadata:`.
+    Metadata,
+    /// Either, picked for each pack.
+    Random,
+}
+
+impl FromStr for PackageOwnerKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top-level" => Ok(Self::TopLevel),
+            "metadata" => Ok(Self::Metadata),
+            "random" => Ok(Self::Random),
+            _ => anyhow::bail!(
+                "unknown package.yml owner key '{}' (expected top-level, metadata or random)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PackageOwnerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TopLevel => "top-level",
+            Self::Metadata => "metadata",
+            Self::Random => "random",
+        })
+    }
+}
+
+impl PackageOwnerKey {
+    /// Whether a pack's owner goes under `metadata:`, drawing from `rng` for [`Self::Random`].
+    pub(crate) fn in_metadata(self, rng: &mut impl Rng) -> bool {
+        match self {
+            Self::TopLevel => false,
+            Self::Metadata => true,
+            Self::Random => rng.gen_bool(0.5),
+        }
+    }
+}
+
+pub(crate) fn product_group(rng: &mut impl Rng) -> &'static str {
+    PRODUCT_GROUPS[rng.gen_range(0..PRODUCT_GROUPS.len())]
+}
+
+/// `package.yml` for `pack` owned by `owner`, with the `metadata` sections in the order given.
+/// `owner_in_metadata` moves the owner to the top of `metadata:`. `product_group` is only used
+/// for [`PackageMetadata::ProductGroup`].
+pub(crate) fn render(
+    pack: &str,
+    owner: &str,
+    owner_in_metadata: bool,
+    metadata: &[PackageMetadata],
+    product_group: Option<&str>,
+) -> String {
+    let mut contents = String::new();
+    if owner_in_metadata {
+        contents.push_str(&format!("metadata:\n  owner: {}\n", owner));
+    } else {
+        contents.push_str(&format!("owner: {}\n", owner));
+        if metadata.is_empty() {
+            return contents;
+        }
+        contents.push_str("metadata:\n");
+    }
+    for (index, section) in metadata.iter().enumerate() {
+        if metadata[..index].contains(section) {
+            continue;
+        }
+        match section {
+            PackageMetadata::Protections => {
+                contents.push_str("  protections:\n");
+                for protection in PROTECTIONS {
+                    contents.push_str(&format!("    {}: fail_on_new\n", protection));
+                }
+            }
+            PackageMetadata::CustomKeys => contents.push_str(&format!(
+                "  slack_channel: \"#{}\"\n  documentation_url: https://wiki.example.com/packs/{}\n  \
+                 stewards:\n    - {}\n",
+                pack, pack, owner
+            )),
+            PackageMetadata::ProductGroup => {
+                if let Some(product_group) = product_group {
+                    contents.push_str(&format!("  product_group: {}\n", product_group));
+                }
+            }
+        }
+    }
+    contents
+}
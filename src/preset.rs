@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, PackNamespaces, PackOwnership, TeamSize};
+
+/// A named bundle of options for a common kind of app, applied over the rest of [`Config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// The smallest app that still exercises everything: one pack per [`PackOwnership`]
+    /// with a single file each, one file per extension in `owned_globs`, and one file for
+    /// each [`ViolationKind`]. Meant for unit-test fixtures rather than load tests.
+    Coverage,
+}
+
+impl FromStr for Preset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "coverage" => Ok(Self::Coverage),
+            _ => anyhow::bail!("unknown preset '{}' (expected coverage)", s),
+        }
+    }
+}
+
+impl Preset {
+    /// Overrides the options the preset decides. Options it doesn't mention are kept.
+    pub(crate) fn apply(self, config: &mut Config) {
+        match self {
+            Self::Coverage => {
+                config.num_packages = COVERAGE_PACKS.len();
+                config.co_owned_percent = 0;
+                config.ownerless_percent = 0;
+                config.team_rename_percent = 0;
+                config.odd_files_percent = 0;
+                config.long_paths_percent = 0;
+                config.case_twins_percent = 0;
+                config.malformed_percent = 0;
+                config.nested_codeowners_percent = 0;
+                config.syntax_error_percent = 0;
+                config.pack_namespaces = PackNamespaces::Flat;
+                config.noise_files = 0;
+                config.pathological_annotations = false;
+                config.team_size = TeamSize::Fixed(1);
+            }
+        }
+    }
+}
+
+/// The packs of a [`Preset::Coverage`] app, in order, named after how they're owned.
+pub(crate) const COVERAGE_PACKS: [(&str, PackOwnership); 5] = [
+    ("directory_owned", PackOwnership::Directory),
+    ("annotation_owned", PackOwnership::FileAnnotation),
+    ("team_config_owned", PackOwnership::TeamConfig),
+    ("pack_config_owned", PackOwnership::PackConfig),
+    ("unowned", PackOwnership::Unowned),
+];
+
+/// One file for each extension `owned_globs` matches besides `.rb`, written to the
+/// directory-owned pack of a coverage app.
+pub(crate) const COVERAGE_EXTENSION_FILES: &[(&str, &str)] = &[
+    ("example.rake", "task :example do\nend\n"),
+    ("example.js", "export const example = 1;\n"),
+    ("example.jsx", "export const Example = () => <div />;\n"),
+    ("example.ts", "export const example: number = 1;\n"),
+    (
+        "example.tsx",
+        "export const Example = (): JSX.Element => <div />;\n",
+    ),
+    ("example.json", "{\"example\": true}\n"),
+    ("example.yml", "example: true\n"),
+];
+
+/// An ownership problem deliberately written into a coverage app, which validation should
+/// report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// A file in a directory-owned pack whose annotation names a different team.
+    MultipleOwners,
+    /// An annotation naming a team that doesn't exist.
+    UnknownTeam,
+    /// A file matched by `owned_globs` that no team owns.
+    Unowned,
+}
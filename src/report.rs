@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::Path,
+};
+
+use crate::{Config, FileTimes, Manifest, PackOwnership};
+
+const REPORT_FILE: &str = "FIXTURE.md";
+
+/// Writes `FIXTURE.md` to the app root: how the app was generated and what is in it, for
+/// whoever is handed the fixture without the command that made it.
+pub(crate) fn write(app_dir: &Path, config: &Config, manifest: &Manifest) -> anyhow::Result<()> {
+    let mut report = String::new();
+    writeln!(report, "# {}\n", manifest.app_name)?;
+    writeln!(
+        report,
+        "Generated by random-rails-generator. Rerun with the same options and `--seed {}` to \
+         reproduce it exactly.\n",
+        manifest.seed
+    )?;
+
+    writeln!(report, "## Configuration\n")?;
+    writeln!(report, "| Option | Value |\n| --- | --- |")?;
+    let options = [
+        ("seed", manifest.seed.to_string()),
+        ("rails", manifest.rails.clone()),
+        ("packs", config.num_packages.to_string()),
+        (
+            "ruby version",
+            config
+                .ruby_version
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        ("team layout", config.team_layout.to_string()),
+        ("team size", config.team_size.to_string()),
+        (
+            "code_ownership.yml shape",
+            manifest.code_ownership_shape.to_string(),
+        ),
+        ("ownership output", config.ownership_output.to_string()),
+        (
+            "codeowners files",
+            list(
+                &config
+                    .codeowners_files
+                    .iter()
+                    .map(|file| format!("{} at {}", file.format, file.path.display()))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        ("extra owned globs", list(&config.extra_owned_globs)),
+        ("extra unowned globs", list(&config.extra_unowned_globs)),
+        ("co-owned packs", format!("{}%", config.co_owned_percent)),
+        ("ownerless packs", format!("{}%", config.ownerless_percent)),
+        ("renamed teams", format!("{}%", config.team_rename_percent)),
+        ("varied annotations", config.varied_annotations.to_string()),
+        (
+            "pathological annotations",
+            config.pathological_annotations.to_string(),
+        ),
+        ("odd files", format!("{}%", config.odd_files_percent)),
+        ("long paths", format!("{}%", config.long_paths_percent)),
+        ("case twins", format!("{}%", config.case_twins_percent)),
+        ("malformed files", format!("{}%", config.malformed_percent)),
+        ("syntax errors", format!("{}%", config.syntax_error_percent)),
+        (
+            "package.yml metadata",
+            list(
+                &config
+                    .package_metadata
+                    .iter()
+                    .map(|metadata| metadata.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        (
+            "package.yml owner key",
+            config.package_owner_key.to_string(),
+        ),
+        (
+            "package roots",
+            list(
+                &config
+                    .package_roots
+                    .iter()
+                    .map(|root| root.dir().to_string())
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        ("pack namespaces", config.pack_namespaces.to_string()),
+        ("pack docs", config.pack_docs.to_string()),
+        ("app code directories", config.app_code_dirs.to_string()),
+        ("spec support", config.spec_support.to_string()),
+        ("noise files", config.noise_files.to_string()),
+        ("infra team", config.infra_team.name.clone()),
+        (
+            "ownership table",
+            config
+                .ownership_table
+                .map_or_else(|| "-".to_string(), |format| format.to_string()),
+        ),
+        (
+            "file times",
+            match config.file_times {
+                Some(FileTimes::Fixed(epoch)) => epoch.to_string(),
+                Some(FileTimes::FromSeed) => "seed".to_string(),
+                None => "-".to_string(),
+            },
+        ),
+    ];
+    for (option, value) in options {
+        writeln!(report, "| {} | {} |", option, value)?;
+    }
+    for tool in &manifest.tools {
+        writeln!(
+            report,
+            "| {} | {} |",
+            tool.name,
+            tool.version.as_deref().unwrap_or(&tool.url)
+        )?;
+    }
+
+    let (files, bytes) = disk_usage(app_dir)?;
+    // Packs of a namespace share its team, and nested .codeowner files can hand a subtree back
+    // to the pack's own team, so teams are counted by name.
+    let teams = manifest
+        .packs
+        .iter()
+        .flat_map(|pack| {
+            pack.team
+                .iter()
+                .chain(pack.co_owner.iter().map(|co_owner| &co_owner.team))
+                .chain(pack.nested_codeowners.iter().map(|nested| &nested.team))
+        })
+        .collect::<BTreeSet<_>>()
+        .len();
+    writeln!(report, "\n## Size\n")?;
+    writeln!(report, "- {} packs", manifest.packs.len())?;
+    writeln!(report, "- {} pack teams, plus infra", teams)?;
+    writeln!(report, "- {} files, {} bytes in total", files, bytes)?;
+
+    let mut mix = BTreeMap::new();
+    for pack in &manifest.packs {
+        *mix.entry(pack.ownership.name()).or_insert(0) += 1;
+    }
+    writeln!(report, "\n## Ownership\n")?;
+    writeln!(report, "| Mechanism | Packs |\n| --- | --- |")?;
+    for (ownership, count) in &mix {
+        writeln!(report, "| {} | {} |", ownership, count)?;
+    }
+    let co_owned = manifest
+        .packs
+        .iter()
+        .filter(|pack| pack.co_owner.is_some())
+        .count();
+    let renamed = manifest
+        .packs
+        .iter()
+        .filter(|pack| pack.previous_team.is_some())
+        .count();
+    let nested = manifest
+        .packs
+        .iter()
+        .filter(|pack| !pack.nested_codeowners.is_empty())
+        .count();
+    writeln!(report, "\n- {} packs split between two teams", co_owned)?;
+    if nested > 0 {
+        writeln!(report, "- {} packs with nested .codeowner files", nested)?;
+    }
+    if renamed > 0 {
+        writeln!(report, "- {} teams renamed, see team-aliases.yml", renamed)?;
+    }
+
+    let count = |files: fn(&crate::PackManifest) -> usize| -> usize {
+        manifest.packs.iter().map(files).sum()
+    };
+    writeln!(report, "\n## Injected faults\n")?;
+    writeln!(
+        report,
+        "- {} pathological annotation files",
+        count(|pack| pack.annotation_edge_cases.len())
+    )?;
+    writeln!(
+        report,
+        "- {} huge, binary or empty files",
+        count(|pack| pack.odd_files.len())
+    )?;
+    writeln!(
+        report,
+        "- {} files at the path length limit",
+        count(|pack| usize::from(pack.long_path_file.is_some()))
+    )?;
+    writeln!(
+        report,
+        "- {} files differing from another only by case",
+        count(|pack| pack.case_twins.len())
+    )?;
+    writeln!(
+        report,
+        "- {} malformed configuration files",
+        count(|pack| usize::from(pack.malformed_file.is_some()))
+    )?;
+    writeln!(
+        report,
+        "- {} Ruby files with syntax errors",
+        count(|pack| pack.syntax_error_files.len())
+    )?;
+    let unowned = manifest
+        .packs
+        .iter()
+        .filter(|pack| pack.ownership == PackOwnership::Unowned)
+        .count();
+    writeln!(report, "- {} packs with no owner", unowned)?;
+    let violations = count(|pack| pack.violations.len());
+    if violations > 0 {
+        writeln!(report, "- {} deliberate ownership violations", violations)?;
+    }
+    writeln!(
+        report,
+        "\nThe exact expected owner of every pack and injected file is in generator-manifest.json."
+    )?;
+
+    std::fs::write(app_dir.join(REPORT_FILE), report)?;
+    Ok(())
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Number of files under `dir` and their total size, not following symlinks.
+pub(crate) fn disk_usage(dir: &Path) -> anyhow::Result<(usize, u64)> {
+    let mut files = 0;
+    let mut bytes = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files += 1;
+                bytes += entry.metadata()?.len();
+            }
+        }
+    }
+    Ok((files, bytes))
+}
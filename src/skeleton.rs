@@ -0,0 +1,113 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use convert_case::{Case, Casing};
+use include_dir::{include_dir, Dir, DirEntry};
+
+/// Trimmed-down copies of what `rails new` produces. `common/` holds the files every version
+/// shares and each `rails-<version>/` directory is written over it. `{{app_name}}`,
+/// `{{app_module}}` and `{{app_title}}` placeholders are filled in when they are written out.
+static SKELETONS: Dir = include_dir!("$CARGO_MANIFEST_DIR/skeletons");
+
+/// Which embedded skeleton [`crate::Config::embedded_skeleton`] writes. The layouts differ in
+/// the ways ownership globs care about, e.g. webpacker's `app/javascript/packs` on 6.1 versus
+/// importmap on 7.x and the solid_* config files on 8.0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RailsFlavor {
+    Rails61,
+    Rails70,
+    Rails72,
+    #[default]
+    Rails80,
+}
+
+impl RailsFlavor {
+    pub fn version(self) -> &'static str {
+        match self {
+            Self::Rails61 => "6.1",
+            Self::Rails70 => "7.0",
+            Self::Rails72 => "7.2",
+            Self::Rails80 => "8.0",
+        }
+    }
+}
+
+impl FromStr for RailsFlavor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "6.1" => Ok(Self::Rails61),
+            "7.0" => Ok(Self::Rails70),
+            "7.2" => Ok(Self::Rails72),
+            "8.0" => Ok(Self::Rails80),
+            _ => anyhow::bail!(
+                "unknown rails flavor '{}' (expected 6.1, 7.0, 7.2 or 8.0)",
+                s
+            ),
+        }
+    }
+}
+
+/// Writes the embedded skeleton for `flavor` to `app_dir`, standing in for `rails new` on
+/// machines without Ruby.
+pub(crate) fn materialize(
+    app_dir: &Path,
+    app_name: &str,
+    flavor: RailsFlavor,
+) -> anyhow::Result<()> {
+    let app_module = app_name.to_case(Case::Pascal);
+    let placeholders = [
+        ("{{app_name}}", app_name),
+        ("{{app_module}}", app_module.as_str()),
+        ("{{app_title}}", app_module.as_str()),
+    ];
+    for layer in ["common".to_string(), format!("rails-{}", flavor.version())] {
+        let dir = SKELETONS
+            .get_dir(&layer)
+            .expect("every skeleton layer is embedded");
+        write_dir(dir, Path::new(&layer), app_dir, &placeholders)?;
+    }
+    Ok(())
+}
+
+fn write_dir(
+    dir: &Dir,
+    layer: &Path,
+    app_dir: &Path,
+    placeholders: &[(&str, &str)],
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(app_dir.join(relative_to_layer(dir.path(), layer)))?;
+    for entry in dir.entries() {
+        match entry {
+            DirEntry::Dir(dir) => write_dir(dir, layer, app_dir, placeholders)?,
+            DirEntry::File(file) => {
+                let relative_path = relative_to_layer(file.path(), layer);
+                let path = app_dir.join(&relative_path);
+                match file.contents_utf8() {
+                    Some(contents) => {
+                        let contents = placeholders
+                            .iter()
+                            .fold(contents.to_string(), |contents, (placeholder, value)| {
+                                contents.replace(placeholder, value)
+                            });
+                        std::fs::write(&path, contents)?;
+                    }
+                    None => std::fs::write(&path, file.contents())?,
+                }
+                if relative_path.starts_with("bin") {
+                    crate::make_executable(&path)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn relative_to_layer(path: &Path, layer: &Path) -> PathBuf {
+    path.strip_prefix(layer)
+        .expect("embedded paths start with their layer")
+        .to_path_buf()
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{annotations::AnnotationStyle, manifest::SupportFile, Config, PackOwnership};
+
+/// Salt for the RNG that picks the teams with their own support files.
+const SPEC_SUPPORT_SEED_SALT: u64 = 0x7370_6563_7375_7070;
+
+/// Most pack teams that get a `spec/support/<team>` directory.
+const MAX_SUPPORT_TEAMS: usize = 3;
+
+/// Shared test infrastructure owned by the infra team through these `owned_globs`.
+pub(crate) const INFRA_SPEC_GLOBS: &[&str] = &[
+    "spec/spec_helper.rb",
+    "spec/rails_helper.rb",
+    "spec/support/*.rb",
+];
+
+const INFRA_FILES: &[(&str, &str)] = &[
+    (
+        "spec/spec_helper.rb",
+        "RSpec.configure do |config|\n  config.expect_with :rspec do |expectations|\n    \
+         expectations.include_chain_clauses_in_custom_matcher_descriptions = true\n  end\n\n  \
+         config.shared_context_metadata_behavior = :apply_to_host_groups\nend\n",
+    ),
+    (
+        "spec/rails_helper.rb",
+        "require 'spec_helper'\nENV['RAILS_ENV'] ||= 'test'\nrequire_relative \
+         '../config/environment'\nrequire 'rspec/rails'\n\n\
+         Dir[Rails.root.join('spec/support/**/*.rb')].sort.each { |f| require f }\n",
+    ),
+    (
+        "spec/support/factory_bot.rb",
+        "RSpec.configure do |config|\n  config.include FactoryBot::Syntax::Methods\nend\n",
+    ),
+    (
+        "spec/support/time_helpers.rb",
+        "RSpec.configure do |config|\n  config.include ActiveSupport::Testing::TimeHelpers\nend\n",
+    ),
+];
+
+/// Shared examples and matchers written for each team with a support directory.
+const TEAM_FILES: &[(&str, &str)] = &[
+    (
+        "shared_examples.rb",
+        "RSpec.shared_examples 'a service' do\n  it { is_expected.to respond_to(:call) }\nend\n",
+    ),
+    (
+        "matchers.rb",
+        "RSpec::Matchers.define :be_processed do\n  match { |actual| actual.processed? }\nend\n",
+    ),
+];
+
+/// Writes `spec/spec_helper.rb`, `spec/rails_helper.rb` and `spec/support`, see
+/// [`Config::spec_support`]. The helpers and top-level support files belong to `infra_team`;
+/// a few of `teams` get a support directory of their own, owned through annotations.
+pub(crate) fn write(
+    config: &Config,
+    seed: u64,
+    infra_team: &str,
+    teams: &[String],
+) -> anyhow::Result<(Vec<SupportFile>, usize)> {
+    let app_dir = config.app_dir();
+    let mut files = Vec::new();
+    for (path, contents) in INFRA_FILES {
+        files.push((PathBuf::from(path), contents.to_string(), infra_team, false));
+    }
+    let mut rng = StdRng::seed_from_u64(seed ^ SPEC_SUPPORT_SEED_SALT);
+    for team in teams.choose_multiple(&mut rng, MAX_SUPPORT_TEAMS) {
+        for (file_name, contents) in TEAM_FILES {
+            let path = PathBuf::from("spec/support").join(team).join(file_name);
+            let contents = format!("{}{}", AnnotationStyle::Plain.header(team), contents);
+            files.push((path, contents, team, true));
+        }
+    }
+
+    let mut support_files = Vec::new();
+    for (path, contents, team, annotated) in files {
+        let full_path = app_dir.join(&path);
+        std::fs::create_dir_all(full_path.parent().expect("support files are below spec"))?;
+        std::fs::write(full_path, contents)?;
+        support_files.push(SupportFile {
+            path,
+            team: team.to_string(),
+            ownership: if annotated {
+                PackOwnership::FileAnnotation
+            } else {
+                PackOwnership::TeamConfig
+            },
+        });
+    }
+    let count = support_files.len();
+    Ok((support_files, count))
+}
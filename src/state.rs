@@ -0,0 +1,67 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::ToolManifest;
+
+const STATE_FILE: &str = ".generator-state.json";
+
+/// Checkpoint written into the app directory while packs are generated. Every random choice
+/// is derived from `seed`, so `seed` plus `completed_packs` is enough to pick generation back
+/// up exactly where it stopped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct GenerationState {
+    pub seed: u64,
+    pub num_packages: usize,
+    pub completed_packs: usize,
+    /// Carried over so a resumed run can still record the downloaded tools in its manifest.
+    #[serde(default)]
+    pub tools: Vec<ToolManifest>,
+}
+
+impl GenerationState {
+    pub fn new(seed: u64, num_packages: usize, tools: Vec<ToolManifest>) -> Self {
+        Self {
+            seed,
+            num_packages,
+            completed_packs: 0,
+            tools,
+        }
+    }
+
+    pub fn path(app_dir: &Path) -> PathBuf {
+        app_dir.join(STATE_FILE)
+    }
+
+    pub fn load(app_dir: &Path) -> anyhow::Result<Self> {
+        let path = Self::path(app_dir);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("no checkpoint to resume from at {}", path.display()))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes to a temporary file first and renames it into place, so a crash mid-write leaves
+    /// the previous checkpoint intact. Both the file and the rename are synced to disk before
+    /// returning, so the checkpoint also survives a power loss. The pack files it counts aren't
+    /// synced, which is why resuming rebuilds the last completed pack.
+    pub fn save(&self, app_dir: &Path) -> anyhow::Result<()> {
+        let path = Self::path(app_dir);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string(self)?.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(tmp_path, path)?;
+        File::open(app_dir)?.sync_all()?;
+        Ok(())
+    }
+
+    pub fn remove(app_dir: &Path) -> anyhow::Result<()> {
+        std::fs::remove_file(Self::path(app_dir))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// A Ruby file that doesn't parse, see [`crate::Config::syntax_error_percent`]. Constant
+/// analysis should report it and carry on with the rest of the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyntaxErrorKind {
+    /// One `end` more than there are blocks to close.
+    UnbalancedEnd,
+    /// A class and method left open at the end of the file.
+    MissingEnd,
+    /// NULs and invalid UTF-8 in the middle of a class body.
+    StrayBytes,
+}
+
+impl SyntaxErrorKind {
+    pub const ALL: [Self; 3] = [Self::UnbalancedEnd, Self::MissingEnd, Self::StrayBytes];
+
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            Self::UnbalancedEnd => "unbalanced_end.rb",
+            Self::MissingEnd => "missing_end.rb",
+            Self::StrayBytes => "stray_bytes.rb",
+        }
+    }
+
+    /// The file's contents, after `annotation` when the pack is annotation-owned. The
+    /// annotation comes first so ownership tools still find it without parsing Ruby.
+    pub(crate) fn contents(self, annotation: Option<&str>) -> Vec<u8> {
+        let mut contents = annotation.unwrap_or_default().as_bytes().to_vec();
+        match self {
+            Self::UnbalancedEnd => contents
+                .extend_from_slice(b"class UnbalancedEnd\n  def call\n    :ok\n  end\nend\nend\n"),
+            Self::MissingEnd => contents.extend_from_slice(
+                b"class MissingEnd\n  def call\n    if ready?\n      :ok\n    end\n",
+            ),
+            Self::StrayBytes => {
+                contents.extend_from_slice(b"class StrayBytes\n  def call\n    ");
+                contents.extend_from_slice(b"\x00\xff\xfe\xc3\x28 :ok\x00\n");
+                contents.extend_from_slice(b"  end\nend\n");
+            }
+        }
+        contents
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// How team YAML is laid out under `config`. Tools find teams through `team_file_glob`, which
+/// is written to match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TeamLayout {
+    /// `config/teams/<team>/<team>-team.yml`, a directory per team.
+    #[default]
+    Nested,
+    /// `config/teams/<team>.yml`, every team file side by side.
+    Flat,
+    /// Every team as its own YAML document in a single `config/teams.yml`.
+    Combined,
+}
+
+impl FromStr for TeamLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nested" => Ok(Self::Nested),
+            "flat" => Ok(Self::Flat),
+            "combined" => Ok(Self::Combined),
+            _ => anyhow::bail!(
+                "unknown team layout '{}' (expected nested, flat or combined)",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TeamLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Nested => "nested",
+            Self::Flat => "flat",
+            Self::Combined => "combined",
+        })
+    }
+}
+
+impl TeamLayout {
+    pub(crate) fn team_file_glob(self) -> &'static str {
+        match self {
+            Self::Nested => "config/teams/**/*.yml",
+            Self::Flat => "config/teams/*.yml",
+            Self::Combined => "config/teams.yml",
+        }
+    }
+
+    pub(crate) fn team_file(self, app_dir: &Path, team: &str) -> PathBuf {
+        match self {
+            Self::Nested => app_dir
+                .join("config/teams")
+                .join(team)
+                .join(format!("{}-team.yml", team)),
+            Self::Flat => app_dir.join("config/teams").join(format!("{}.yml", team)),
+            Self::Combined => app_dir.join("config/teams.yml"),
+        }
+    }
+
+    pub(crate) fn exists(self, app_dir: &Path, team: &str) -> anyhow::Result<bool> {
+        match self {
+            Self::Nested => Ok(app_dir.join("config/teams").join(team).exists()),
+            Self::Flat => Ok(self.team_file(app_dir, team).exists()),
+            Self::Combined => Ok(combined_documents(&self.team_file(app_dir, team))?
+                .iter()
+                .any(|document| is_team_document(document, team))),
+        }
+    }
+
+    /// Writes `yaml` as the definition of `team`. In the combined layout it is appended as a
+    /// new document.
+    pub(crate) fn write(self, app_dir: &Path, team: &str, yaml: &str) -> anyhow::Result<()> {
+        let path = self.team_file(app_dir, team);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if self != Self::Combined {
+            std::fs::write(path, yaml)?;
+            return Ok(());
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        write!(file, "---\n{}\n", document_body(yaml))?;
+        Ok(())
+    }
+
+    pub(crate) fn remove(self, app_dir: &Path, team: &str) -> anyhow::Result<()> {
+        let path = self.team_file(app_dir, team);
+        match self {
+            Self::Nested => {
+                let team_dir = app_dir.join("config/teams").join(team);
+                if team_dir.exists() {
+                    std::fs::remove_dir_all(team_dir)?;
+                }
+            }
+            Self::Flat => {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+            Self::Combined => {
+                let kept = combined_documents(&path)?
+                    .into_iter()
+                    .filter(|document| !is_team_document(document, team))
+                    .map(|document| format!("---\n{}\n", document))
+                    .collect::<String>();
+                if path.exists() {
+                    std::fs::write(path, kept)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The teams defined in an app, for checking and changing them pack after pack. In the combined
+/// layout `config/teams.yml` is parsed once, when this is loaded, rather than for every pack.
+pub(crate) struct Teams {
+    layout: TeamLayout,
+    app_dir: PathBuf,
+    /// Names defined in `config/teams.yml`, kept only for [`TeamLayout::Combined`].
+    combined: Option<HashSet<String>>,
+}
+
+impl Teams {
+    pub(crate) fn load(layout: TeamLayout, app_dir: &Path) -> anyhow::Result<Self> {
+        let combined = match layout {
+            TeamLayout::Combined => Some(
+                combined_documents(&layout.team_file(app_dir, ""))?
+                    .iter()
+                    .flat_map(|document| {
+                        document
+                            .lines()
+                            .filter_map(|line| line.strip_prefix("name: "))
+                            .map(str::to_string)
+                            .collect::<Vec<_>>()
+                    })
+                    .collect(),
+            ),
+            TeamLayout::Nested | TeamLayout::Flat => None,
+        };
+        Ok(Self {
+            layout,
+            app_dir: app_dir.to_path_buf(),
+            combined,
+        })
+    }
+
+    pub(crate) fn exists(&self, team: &str) -> anyhow::Result<bool> {
+        match &self.combined {
+            Some(teams) => Ok(teams.contains(team)),
+            None => self.layout.exists(&self.app_dir, team),
+        }
+    }
+
+    /// See [`TeamLayout::write`].
+    pub(crate) fn write(&mut self, team: &str, yaml: &str) -> anyhow::Result<()> {
+        self.layout.write(&self.app_dir, team, yaml)?;
+        if let Some(teams) = &mut self.combined {
+            teams.insert(team.to_string());
+        }
+        Ok(())
+    }
+
+    /// Removes `team`'s definition. The combined file is only rewritten when it defines `team`.
+    pub(crate) fn remove(&mut self, team: &str) -> anyhow::Result<()> {
+        if let Some(teams) = &mut self.combined {
+            if !teams.remove(team) {
+                return Ok(());
+            }
+        }
+        self.layout.remove(&self.app_dir, team)
+    }
+}
+
+/// A team's YAML without the surrounding blank lines or its own `---` marker.
+fn document_body(yaml: &str) -> &str {
+    let yaml = yaml.trim();
+    yaml.strip_prefix("---").unwrap_or(yaml).trim()
+}
+
+fn combined_documents(path: &Path) -> anyhow::Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(std::fs::read_to_string(path)?
+        .split("---\n")
+        .map(str::trim)
+        .filter(|document| !document.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn is_team_document(document: &str, team: &str) -> bool {
+    document
+        .lines()
+        .any(|line| line.strip_prefix("name: ") == Some(team))
+}
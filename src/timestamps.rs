@@ -0,0 +1,87 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Salt for the RNG behind [`FileTimes::FromSeed`], so the times don't correlate with the
+/// packs generated from the same seed.
+const TIMES_SEED_SALT: u64 = 0x6D74_696D_6573_2121;
+
+/// Start of the range seed-derived times are drawn from: 2020-01-01T00:00:00Z.
+const SEED_TIMES_START: u64 = 1_577_836_800;
+/// Length of that range, one year.
+const SEED_TIMES_SPAN: u64 = 365 * 24 * 60 * 60;
+
+/// What every generated file's and directory's mtime is set to once the app is written, see
+/// [`crate::Config::file_times`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTimes {
+    /// This many seconds after the Unix epoch, for every path.
+    Fixed(u64),
+    /// A different time per path within 2020, drawn from the run's seed in path order.
+    FromSeed,
+}
+
+impl FromStr for FileTimes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "seed" {
+            return Ok(Self::FromSeed);
+        }
+        match s.parse() {
+            Ok(epoch) => Ok(Self::Fixed(epoch)),
+            Err(_) => anyhow::bail!(
+                "invalid file times '{}' (expected seconds since the Unix epoch or seed)",
+                s
+            ),
+        }
+    }
+}
+
+impl FileTimes {
+    /// Sets the mtime of everything under `app_dir`, and of `app_dir` itself. Paths are visited
+    /// in sorted order so seed-derived times don't depend on directory listing order.
+    pub(crate) fn apply(self, app_dir: &Path, seed: u64) -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed ^ TIMES_SEED_SALT);
+        let mut paths = walk(app_dir, true)?;
+        paths.push(app_dir.to_path_buf());
+        for path in paths {
+            let secs = match self {
+                Self::Fixed(epoch) => epoch,
+                Self::FromSeed => SEED_TIMES_START + rng.gen_range(0..SEED_TIMES_SPAN),
+            };
+            set_mtime(&path, UNIX_EPOCH + Duration::from_secs(secs))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn set_mtime(path: &Path, time: SystemTime) -> anyhow::Result<()> {
+    File::open(path)?.set_modified(time)?;
+    Ok(())
+}
+
+/// Every file under `dir`, and every directory too when `with_dirs` is set, sorted and not
+/// following symlinks. Directories come after their contents.
+pub(crate) fn walk(dir: &Path, with_dirs: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            paths.extend(walk(&entry.path(), with_dirs)?);
+            if with_dirs {
+                paths.push(entry.path());
+            }
+        } else if file_type.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
@@ -0,0 +1,137 @@
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+/// Commands run against a freshly generated app, relative to the app directory, with the
+/// name the tool has in [`crate::Manifest::tools`]. Both are expected to succeed on a
+/// well-formed fixture. Neither changes the app.
+pub(crate) const TOOL_COMMANDS: &[(&str, &str, &[&str])] = &[
+    ("codeowners", ".dotslash/codeowners-rs", &["validate"]),
+    ("pks", ".dotslash/pks", &["validate"]),
+];
+
+/// Writes `.github/CODEOWNERS` for apps that don't get one from the generator, which
+/// codeowners-rs' `validate` requires.
+const GENERATE_COMMAND: (&str, &[&str]) = (".dotslash/codeowners-rs", &["generate"]);
+
+/// Asks which team owns a file, given its path relative to the app. See [`file_owner`].
+const OWNER_COMMAND: (&str, &[&str]) = (".dotslash/codeowners-rs", &["for-file"]);
+
+/// What codeowners-rs prints as the team of a file nobody owns.
+const UNOWNED_TEAM: &str = "Unowned";
+
+/// Exit code Rust binaries use when they panic.
+const PANIC_EXIT_CODE: i32 = 101;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolRun {
+    pub command: String,
+    pub outcome: ToolOutcome,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ToolOutcome {
+    Passed,
+    /// The tool ran to completion but reported problems with the app.
+    Failed {
+        exit_code: i32,
+        stderr: String,
+    },
+    /// The tool could not be started, panicked, or was killed by a signal.
+    Crashed {
+        reason: String,
+    },
+}
+
+/// Runs [`TOOL_COMMANDS`], after [`GENERATE_COMMAND`] when `generate_codeowners` is set. A
+/// hand-written CODEOWNERS is never replaced.
+pub(crate) fn run_tools(app_dir: &Path, generate_codeowners: bool) -> Vec<ToolRun> {
+    let generate = generate_codeowners.then_some(GENERATE_COMMAND);
+    generate
+        .into_iter()
+        .chain(
+            TOOL_COMMANDS
+                .iter()
+                .map(|(_, program, args)| (*program, *args)),
+        )
+        .map(|(program, args)| run_tool(app_dir, program, args).0)
+        .collect()
+}
+
+/// Runs one tool, returning the run along with its exact wall-clock time.
+pub(crate) fn run_tool(app_dir: &Path, program: &str, args: &[&str]) -> (ToolRun, Duration) {
+    let (run, elapsed, _) = execute(app_dir, program, args);
+    (run, elapsed)
+}
+
+/// The team codeowners-rs reports for `path`, read from the `Team:` line it prints, or `None`
+/// when it reports the file as unowned. Fails with the run when the tool doesn't pass.
+pub(crate) fn file_owner(app_dir: &Path, path: &Path) -> Result<Option<String>, ToolRun> {
+    let (program, args) = OWNER_COMMAND;
+    let path = path.to_string_lossy();
+    let args = args
+        .iter()
+        .copied()
+        .chain(std::iter::once(path.as_ref()))
+        .collect::<Vec<_>>();
+    let (run, _, stdout) = execute(app_dir, program, &args);
+    if run.outcome != ToolOutcome::Passed {
+        return Err(run);
+    }
+    let team = String::from_utf8_lossy(&stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("Team:"))
+        .map(|team| team.trim().to_string());
+    Ok(team.filter(|team| team != UNOWNED_TEAM))
+}
+
+fn execute(app_dir: &Path, program: &str, args: &[&str]) -> (ToolRun, Duration, Vec<u8>) {
+    // Relative programs are resolved against the child's working directory on some
+    // platforms, so make sure it's absolute before changing into the app.
+    let program_path = app_dir.join(program);
+    let program_path = std::path::absolute(&program_path).unwrap_or(program_path);
+    let started = Instant::now();
+    let mut stdout = Vec::new();
+    let outcome = match Command::new(program_path)
+        .args(args)
+        .current_dir(app_dir)
+        .output()
+    {
+        Err(err) => ToolOutcome::Crashed {
+            reason: format!("failed to start: {}", err),
+        },
+        Ok(output) if output.status.success() => {
+            stdout = output.stdout;
+            ToolOutcome::Passed
+        }
+        Ok(output) => match output.status.code() {
+            Some(PANIC_EXIT_CODE) | None => ToolOutcome::Crashed {
+                reason: format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            },
+            Some(exit_code) => ToolOutcome::Failed {
+                exit_code,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            },
+        },
+    };
+    let elapsed = started.elapsed();
+    let run = ToolRun {
+        command: std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" "),
+        outcome,
+        duration_ms: elapsed.as_millis(),
+    };
+    (run, elapsed, stdout)
+}